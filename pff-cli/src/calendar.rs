@@ -0,0 +1,649 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc, Weekday};
+use itertools::Itertools;
+use pff::{
+    appointment::{Appointment, BusyStatus},
+    filetime::nearest_date,
+    item::{AnyItem, ItemExt},
+    recurrence::{
+        AppointmentRecurrence, Frequency, Pattern, RecurrenceEnd, RecurrencePattern, TimeZone,
+        Transition, Weekdays,
+    },
+    FileOpenFlags, Pff,
+};
+
+use crate::{
+    content_lines::{self, ContentLines},
+    folders,
+};
+
+/// Identifies the program that wrote a calendar.
+pub(crate) const PRODUCT_ID: &str = "-//pff-tools//pff-cli//EN";
+
+pub(crate) async fn run(pff_file: PathBuf, dest: PathBuf) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    let mut exported = 0;
+    let mut failed = 0;
+
+    if let Some(root_folder) = pff.root_folder()? {
+        let root_folder = root_folder.into_folder()?;
+
+        // every folder that holds appointments gets its own .ics file with
+        // all of the folder's appointments in it
+        folders::for_each_folder(&root_folder, &dest, "ics", &mut |folder, path| {
+            let mut events = String::new();
+            let mut time_zones = TimeZones::default();
+
+            for message in folder.messages()? {
+                let AnyItem::Appointment(appointment) = message?.classify()? else {
+                    continue;
+                };
+
+                match to_vevents(&appointment, &mut time_zones) {
+                    Ok(event) => {
+                        events.push_str(&event);
+                        exported += 1;
+                    }
+                    Err(_) => failed += 1,
+                }
+
+                print!("Exported {exported} appointments\r");
+                io::stdout().flush()?;
+            }
+
+            if !events.is_empty() {
+                write_calendar(&path, &(time_zones.into_string() + &events))?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    println!("\nDone. Exported {exported} appointments, {failed} failed.");
+
+    Ok(())
+}
+
+/// Writes `components` wrapped in a VCALENDAR to `path`.
+pub(crate) fn write_calendar(path: &std::path::Path, components: &str) -> Result<()> {
+    let mut header = ContentLines::new();
+    header.raw("BEGIN", "VCALENDAR");
+    header.raw("VERSION", "2.0");
+    header.raw("PRODID", PRODUCT_ID);
+
+    let mut footer = ContentLines::new();
+    footer.raw("END", "VCALENDAR");
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        path,
+        format!(
+            "{}{components}{}",
+            header.into_string(),
+            footer.into_string()
+        ),
+    )?;
+
+    Ok(())
+}
+
+/// Converts the local times of a recurrence to UTC.
+enum Clock {
+    TimeZone(TimeZone),
+    /// The difference between local time and UTC when there is no time
+    /// zone, worked out from the start time of the series.
+    Offset(Duration),
+}
+
+impl Clock {
+    fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            Clock::TimeZone(time_zone) => time_zone.to_utc(local),
+            Clock::Offset(offset) => local - *offset,
+        }
+    }
+
+    /// The minutes local time is ahead of UTC in standard and in daylight
+    /// saving time.
+    fn offsets(&self) -> (i32, i32) {
+        match self {
+            Clock::TimeZone(time_zone) => (
+                -(time_zone.bias + time_zone.standard_bias),
+                -(time_zone.bias + time_zone.daylight_bias),
+            ),
+            Clock::Offset(offset) => (offset.num_minutes() as i32, offset.num_minutes() as i32),
+        }
+    }
+
+    /// A name for the time zone when the appointment doesn't have a
+    /// description of it, for e.g. `UTC+0100`.
+    fn name(&self) -> String {
+        format!("UTC{}", format_offset(self.offsets().0))
+    }
+
+    /// Renders the STANDARD and DAYLIGHT components of a VTIMEZONE.
+    fn to_observances(&self) -> String {
+        let mut lines = ContentLines::new();
+        let (standard, daylight) = self.offsets();
+
+        let transitions = match self {
+            Clock::TimeZone(TimeZone {
+                standard_date: Some(standard_date),
+                daylight_date: Some(daylight_date),
+                ..
+            }) => observance(standard_date).zip(observance(daylight_date)),
+            _ => None,
+        };
+        match transitions {
+            Some(((standard_start, standard_rule), (daylight_start, daylight_rule))) => {
+                let rules = [
+                    (
+                        "STANDARD",
+                        standard_start,
+                        standard_rule,
+                        daylight,
+                        standard,
+                    ),
+                    (
+                        "DAYLIGHT",
+                        daylight_start,
+                        daylight_rule,
+                        standard,
+                        daylight,
+                    ),
+                ];
+                for (name, start, rule, from, to) in rules {
+                    lines.raw("BEGIN", name);
+                    lines.raw("DTSTART", &format_local_time(start));
+                    lines.raw("RRULE", &rule);
+                    lines.raw("TZOFFSETFROM", &format_offset(from));
+                    lines.raw("TZOFFSETTO", &format_offset(to));
+                    lines.raw("END", name);
+                }
+            }
+            None => {
+                lines.raw("BEGIN", "STANDARD");
+                lines.raw("DTSTART", "16010101T000000");
+                lines.raw("TZOFFSETFROM", &format_offset(standard));
+                lines.raw("TZOFFSETTO", &format_offset(standard));
+                lines.raw("END", "STANDARD");
+            }
+        }
+
+        lines.into_string()
+    }
+}
+
+/// The first onset of a daylight saving time transition and the rule it
+/// recurs by. Transitions are taken to happen every year, the same way
+/// `TimeZone::to_utc` does.
+fn observance(transition: &Transition) -> Option<(NaiveDateTime, String)> {
+    let start = transition.time(1601)?;
+    let day = match transition.year {
+        Some(_) => format!("BYMONTHDAY={}", start.day()),
+        None => {
+            let week = if transition.day >= 5 {
+                -1
+            } else {
+                transition.day as i32
+            };
+            format!("BYDAY={week}{}", day_name(transition.day_of_week))
+        }
+    };
+
+    Some((
+        start,
+        format!("FREQ=YEARLY;BYMONTH={};{day}", transition.month),
+    ))
+}
+
+/// The VTIMEZONEs the events of a calendar refer to.
+#[derive(Default)]
+struct TimeZones {
+    /// The TZID and the rendered observances of each time zone.
+    time_zones: Vec<(String, String)>,
+}
+
+impl TimeZones {
+    /// Returns the TZID of the VTIMEZONE for `clock`, adding it if it's not
+    /// there yet. Time zones that share a name but not their rules are told
+    /// apart by a number.
+    fn add(&mut self, name: &str, clock: &Clock) -> String {
+        let observances = clock.to_observances();
+        // the TZID is quoted where it's used as a parameter and should read
+        // the same without unescaping, e.g. "(UTC+01:00) Amsterdam, Berlin"
+        // becomes "(UTC+01:00) Amsterdam Berlin"
+        let name = name
+            .chars()
+            .filter(|c| !matches!(c, '"' | ',' | ';' | '\\') && !c.is_control())
+            .collect::<String>();
+
+        let mut tzid = name.clone();
+        for n in 2.. {
+            match self.time_zones.iter().find(|(id, _)| *id == tzid) {
+                Some((_, existing)) if *existing == observances => return tzid,
+                Some(_) => tzid = format!("{name} ({n})"),
+                None => break,
+            }
+        }
+
+        self.time_zones.push((tzid.clone(), observances));
+        tzid
+    }
+
+    fn into_string(self) -> String {
+        let mut out = String::new();
+        for (tzid, observances) in self.time_zones {
+            let mut lines = ContentLines::new();
+            lines.raw("BEGIN", "VTIMEZONE");
+            lines.raw("TZID", &tzid);
+            out.push_str(&lines.into_string());
+            out.push_str(&observances);
+
+            let mut lines = ContentLines::new();
+            lines.raw("END", "VTIMEZONE");
+            out.push_str(&lines.into_string());
+        }
+        out
+    }
+}
+
+/// Renders an appointment as a VEVENT (RFC 5545), followed by a VEVENT for
+/// each modified instance if it's a recurring appointment.
+fn to_vevents(appointment: &Appointment, time_zones: &mut TimeZones) -> Result<String> {
+    let mut event = ContentLines::new();
+    event.raw("BEGIN", "VEVENT");
+
+    let uid = match appointment.global_object_id()? {
+        Some(id) => id.iter().map(|b| format!("{b:02X}")).join(""),
+        None => format!("{}@pff-cli", appointment.id()?),
+    };
+    event.text("UID", &uid);
+
+    let stamp = appointment
+        .modification_time()?
+        .or(appointment.creation_time()?)
+        .unwrap_or_else(|| Utc::now().naive_utc());
+    event.time("DTSTAMP", stamp);
+
+    let all_day = appointment.is_all_day()?;
+    let start_time = appointment.start_time()?;
+    let end_time = appointment.end_time()?;
+    let recurrence = match appointment.is_recurring()? {
+        true => appointment.recurrence()?,
+        false => None,
+    };
+    let clock = match (appointment.time_zone()?, &recurrence, start_time) {
+        (Some(time_zone), _, _) => Some(Clock::TimeZone(time_zone)),
+        (None, Some(recurrence), Some(start_time)) => {
+            Some(Clock::Offset(recurrence.start_time() - start_time))
+        }
+        _ => None,
+    };
+
+    let subject = appointment.subject()?;
+    let location = appointment.location()?;
+    let busy_status = appointment.busy_status()?;
+
+    // recurrences are expanded in the time zone of DTSTART so the times of
+    // a recurring appointment are written in its local time zone
+    let mut exceptions = String::new();
+    if let (Some(recurrence), Some(clock)) = (&recurrence, &clock) {
+        let tzid = match all_day {
+            true => None,
+            false => {
+                let name = appointment
+                    .timezone_description()?
+                    .filter(|name| !name.trim().is_empty())
+                    .unwrap_or_else(|| clock.name());
+                Some(time_zones.add(name.trim(), clock))
+            }
+        };
+        write_recurrence(&mut event, recurrence, clock, tzid.as_deref());
+        exceptions = to_exception_vevents(
+            recurrence,
+            tzid.as_deref(),
+            &uid,
+            stamp,
+            (subject.as_deref(), location.as_deref(), busy_status),
+        );
+    } else {
+        // without a time zone the closest we get to the local date of an
+        // all day event is the nearest day
+        if let Some(start_time) = start_time {
+            let date = nearest_date(start_time);
+            write_times(&mut event, all_day, "DTSTART", date, start_time);
+        }
+        if let Some(end_time) = end_time {
+            let date = nearest_date(end_time);
+            write_times(&mut event, all_day, "DTEND", date, end_time);
+        }
+    }
+
+    event.opt_text("SUMMARY", subject.as_deref());
+    event.opt_text("LOCATION", location.as_deref());
+    event.opt_text("DESCRIPTION", appointment.body()?.as_deref());
+    write_busy_status(&mut event, busy_status);
+
+    event.raw("END", "VEVENT");
+
+    Ok(event.into_string() + &exceptions)
+}
+
+/// Writes the start and end of the first instance of a recurrence and the
+/// rule and deleted dates the other instances follow from. The times are
+/// local times in the time zone `tzid`, or dates for all day events (when
+/// `tzid` is `None`).
+fn write_recurrence(
+    event: &mut ContentLines,
+    recurrence: &AppointmentRecurrence,
+    clock: &Clock,
+    tzid: Option<&str>,
+) {
+    let instance = |date: NaiveDate| recurrence.instance_start(date);
+
+    write_local_time(event, tzid, "DTSTART", recurrence.start_time());
+    write_local_time(event, tzid, "DTEND", recurrence.end_time());
+
+    // UNTIL has to be in UTC when DTSTART has a time zone
+    let until = match (recurrence.pattern.end, tzid) {
+        (RecurrenceEnd::Date(date), None) => Some(format_date(date)),
+        (RecurrenceEnd::Date(date), Some(_)) => {
+            Some(content_lines::format_time(clock.to_utc(instance(date))))
+        }
+        _ => None,
+    };
+    if let Some(rule) = rrule(&recurrence.pattern, until) {
+        event.raw("RRULE", &rule);
+    }
+
+    for date in recurrence.deleted_dates() {
+        write_local_time(event, tzid, "EXDATE", instance(date));
+    }
+}
+
+/// Renders the modified instances of a recurring appointment, each as a
+/// VEVENT that overrides the instance it replaces.
+fn to_exception_vevents(
+    recurrence: &AppointmentRecurrence,
+    tzid: Option<&str>,
+    uid: &str,
+    stamp: NaiveDateTime,
+    (subject, location, busy_status): (Option<&str>, Option<&str>, Option<BusyStatus>),
+) -> String {
+    let mut events = String::new();
+
+    for exception in &recurrence.exceptions {
+        let mut event = ContentLines::new();
+        event.raw("BEGIN", "VEVENT");
+        event.text("UID", uid);
+        event.time("DTSTAMP", stamp);
+
+        let original = exception.original_start_time;
+        write_local_time(&mut event, tzid, "RECURRENCE-ID", original);
+        write_local_time(&mut event, tzid, "DTSTART", exception.start_time);
+        write_local_time(&mut event, tzid, "DTEND", exception.end_time);
+
+        event.opt_text("SUMMARY", exception.subject.as_deref().or(subject));
+        event.opt_text("LOCATION", exception.location.as_deref().or(location));
+        write_busy_status(&mut event, exception.busy_status.or(busy_status));
+
+        event.raw("END", "VEVENT");
+        events.push_str(&event.into_string());
+    }
+
+    events
+}
+
+/// Writes a local date-time property in the time zone `tzid`, or a date
+/// property for all day events (when `tzid` is `None`).
+fn write_local_time(
+    event: &mut ContentLines,
+    tzid: Option<&str>,
+    name: &str,
+    local: NaiveDateTime,
+) {
+    match tzid {
+        Some(tzid) => event.raw(
+            &format!("{name};TZID=\"{tzid}\""),
+            &format_local_time(local),
+        ),
+        None => event.raw(&format!("{name};VALUE=DATE"), &format_date(local.date())),
+    }
+}
+
+/// Writes a date property for all day events and a UTC date-time property
+/// for all others.
+fn write_times(
+    event: &mut ContentLines,
+    all_day: bool,
+    name: &str,
+    date: NaiveDate,
+    utc: NaiveDateTime,
+) {
+    match all_day {
+        true => event.raw(&format!("{name};VALUE=DATE"), &format_date(date)),
+        false => event.time(name, utc),
+    }
+}
+
+fn write_busy_status(event: &mut ContentLines, busy_status: Option<BusyStatus>) {
+    let Some(busy_status) = busy_status else {
+        return;
+    };
+
+    let (transparency, status) = match busy_status {
+        BusyStatus::Free => ("TRANSPARENT", "FREE"),
+        BusyStatus::Tentative => ("OPAQUE", "TENTATIVE"),
+        BusyStatus::Busy => ("OPAQUE", "BUSY"),
+        BusyStatus::OutOfOffice => ("OPAQUE", "OOF"),
+        BusyStatus::WorkingElsewhere => ("OPAQUE", "WORKINGELSEWHERE"),
+    };
+    event.raw("TRANSP", transparency);
+    // the property Outlook uses to keep the busy status when importing
+    event.raw("X-MICROSOFT-CDO-BUSYSTATUS", status);
+}
+
+pub(crate) fn format_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Formats `time` as a date-time without a time zone, for e.g.
+/// `20220301T100000`.
+fn format_local_time(time: NaiveDateTime) -> String {
+    time.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// Formats the minutes that local time is ahead of UTC, for e.g. `+0100`.
+fn format_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+/// Turns a recurrence pattern into the value of an RRULE property. `until`
+/// is the formatted end of the recurrence if it ends after a date. Returns
+/// `None` for patterns that RRULE can't express, i.e., Hijri calendar ones.
+pub(crate) fn rrule(pattern: &RecurrencePattern, until: Option<String>) -> Option<String> {
+    if pattern.hijri {
+        return None;
+    }
+
+    let by_day = |days: Weekdays| days.days().into_iter().map(day_name).join(",");
+    let interval = |interval: u32| (interval > 1).then(|| format!("INTERVAL={interval}"));
+    let by_month_day = |pattern: Pattern| match pattern {
+        // day 31 stands for the last day of the month
+        Pattern::Month(31) | Pattern::MonthEnd => vec!["BYMONTHDAY=-1".to_string()],
+        Pattern::Month(day) => vec![format!("BYMONTHDAY={day}")],
+        Pattern::MonthNth(days, n) => vec![
+            format!("BYDAY={}", by_day(days)),
+            format!("BYSETPOS={}", if n >= 5 { -1 } else { n as i32 }),
+        ],
+        Pattern::Day | Pattern::Week(_) => vec![],
+    };
+
+    let mut parts = match (pattern.frequency, pattern.pattern) {
+        // the period of daily recurrences is in minutes
+        (_, Pattern::Day) => ["FREQ=DAILY".to_string()]
+            .into_iter()
+            .chain(interval(pattern.period / 1440))
+            .collect(),
+        // "every weekday" is a daily recurrence with a week pattern
+        (Frequency::Daily, Pattern::Week(days)) => {
+            vec!["FREQ=WEEKLY".to_string(), format!("BYDAY={}", by_day(days))]
+        }
+        (_, Pattern::Week(days)) => ["FREQ=WEEKLY".to_string()]
+            .into_iter()
+            .chain(interval(pattern.period))
+            .chain([
+                format!("BYDAY={}", by_day(days)),
+                format!("WKST={}", day_name(pattern.first_day_of_week)),
+            ])
+            .collect(),
+        // the period of yearly recurrences is in months
+        (Frequency::Yearly, month_pattern) => ["FREQ=YEARLY".to_string()]
+            .into_iter()
+            .chain(interval(pattern.period / 12))
+            .chain([format!("BYMONTH={}", pattern.start_date.month())])
+            .chain(by_month_day(month_pattern))
+            .collect(),
+        (_, month_pattern) => ["FREQ=MONTHLY".to_string()]
+            .into_iter()
+            .chain(interval(pattern.period))
+            .chain(by_month_day(month_pattern))
+            .collect::<Vec<_>>(),
+    };
+
+    match pattern.end {
+        RecurrenceEnd::Date(_) => parts.extend(until.map(|until| format!("UNTIL={until}"))),
+        RecurrenceEnd::Count(count) => parts.push(format!("COUNT={count}")),
+        RecurrenceEnd::Never => {}
+    }
+
+    Some(parts.join(";"))
+}
+
+fn day_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime, Weekday};
+    use pff::recurrence::{
+        AppointmentRecurrence, Frequency, Pattern, RecurrenceEnd, RecurrenceException,
+        RecurrencePattern, TimeZone, Transition, Weekdays,
+    };
+
+    use crate::{
+        calendar::{to_exception_vevents, write_recurrence, Clock, TimeZones},
+        content_lines::ContentLines,
+    };
+
+    fn time(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn transition(month: u32, hour: u32) -> Option<Transition> {
+        Some(Transition {
+            year: None,
+            month,
+            day_of_week: Weekday::Sun,
+            day: 5,
+            hour,
+            minute: 0,
+        })
+    }
+
+    #[test]
+    fn recurrence_across_dst() {
+        // W. Europe Standard Time, daylight saving time starts on 2022-03-27
+        let clock = Clock::TimeZone(TimeZone {
+            bias: -60,
+            standard_bias: 0,
+            daylight_bias: -60,
+            standard_date: transition(10, 3),
+            daylight_date: transition(3, 2),
+        });
+
+        // every Monday at 00:30, which is still Sunday in UTC
+        let recurrence = AppointmentRecurrence {
+            pattern: RecurrencePattern {
+                frequency: Frequency::Weekly,
+                pattern: Pattern::Week(Weekdays::MONDAY),
+                hijri: false,
+                period: 1,
+                end: RecurrenceEnd::Count(5),
+                first_day_of_week: Weekday::Sun,
+                deleted_instance_dates: vec![time(4, 4, 0, 0).date(), time(4, 11, 0, 0).date()],
+                modified_instance_dates: vec![time(4, 11, 0, 0).date()],
+                start_date: time(3, 21, 0, 0).date(),
+                end_date: time(4, 18, 0, 0).date(),
+            },
+            start_time_offset: 30,
+            end_time_offset: 90,
+            exceptions: vec![RecurrenceException {
+                start_time: time(4, 11, 10, 0),
+                end_time: time(4, 11, 11, 0),
+                original_start_time: time(4, 11, 0, 30),
+                subject: None,
+                location: None,
+                busy_status: None,
+            }],
+        };
+
+        let mut time_zones = TimeZones::default();
+        let tzid = time_zones.add("(UTC+01:00) Amsterdam, Berlin", &clock);
+        assert_eq!(tzid, "(UTC+01:00) Amsterdam Berlin");
+        assert_eq!(
+            time_zones.add("(UTC+01:00) Amsterdam, Berlin", &clock),
+            tzid
+        );
+        let time_zones = time_zones.into_string();
+        assert!(time_zones.contains("TZID:(UTC+01:00) Amsterdam Berlin\r\n"));
+        assert!(time_zones.contains("RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n"));
+        assert!(time_zones.contains("TZOFFSETFROM:+0100\r\nTZOFFSETTO:+0200\r\n"));
+
+        let mut event = ContentLines::new();
+        write_recurrence(&mut event, &recurrence, &clock, Some(&tzid));
+        let event = event.into_string();
+        let tzid = "TZID=\"(UTC+01:00) Amsterdam Berlin\"";
+        assert!(event.contains(&format!("DTSTART;{tzid}:20220321T003000\r\n")));
+        assert!(event.contains("RRULE:FREQ=WEEKLY;BYDAY=MO;WKST=SU;COUNT=5\r\n"));
+        // after the change the instance is still at 00:30 local time
+        assert!(event.contains(&format!("EXDATE;{tzid}:20220404T003000\r\n")));
+        assert!(!event.contains("20220411"));
+
+        let exceptions = to_exception_vevents(
+            &recurrence,
+            Some("(UTC+01:00) Amsterdam Berlin"),
+            "uid",
+            time(3, 1, 0, 0),
+            (None, None, None),
+        );
+        assert!(exceptions.contains(&format!("RECURRENCE-ID;{tzid}:20220411T003000\r\n")));
+        assert!(exceptions.contains(&format!("DTSTART;{tzid}:20220411T100000\r\n")));
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use itertools::Itertools;
+use pff::{
+    contact::Contact,
+    distributionlist::{DistributionList, DistributionListMember},
+    item::{AnyItem, ItemExt},
+    FileOpenFlags, Pff, PffOpen,
+};
+
+use crate::{
+    content_lines::{self, ContentLines},
+    folders,
+};
+
+pub(crate) async fn run(pff_file: PathBuf, dest: PathBuf) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    let mut exported = 0;
+    let mut failed = 0;
+
+    if let Some(root_folder) = pff.root_folder()? {
+        let root_folder = root_folder.into_folder()?;
+
+        // every folder that holds contacts gets its own .vcf file with all of
+        // the folder's contacts and distribution lists in it
+        folders::for_each_folder(&root_folder, &dest, "vcf", &mut |folder, path| {
+            let mut cards = String::new();
+
+            for message in folder.messages()? {
+                let card = match message?.classify()? {
+                    AnyItem::Contact(contact) => to_vcard(&contact),
+                    AnyItem::DistributionList(list) => to_group_vcard(&pff, &list),
+                    _ => continue,
+                };
+
+                match card {
+                    Ok(card) => {
+                        cards.push_str(&card);
+                        exported += 1;
+                    }
+                    Err(_) => failed += 1,
+                }
+
+                print!("Exported {exported} contacts\r");
+                io::stdout().flush()?;
+            }
+
+            if !cards.is_empty() {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, cards)?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    println!("\nDone. Exported {exported} contacts, {failed} failed.");
+
+    Ok(())
+}
+
+/// Renders a contact as a vCard 4.0 (RFC 6350) card.
+fn to_vcard(contact: &Contact) -> Result<String> {
+    let mut card = ContentLines::new();
+    card.raw("BEGIN", "VCARD");
+    card.raw("VERSION", "4.0");
+    card.raw("UID", &uid(contact.id()?));
+
+    let given_name = contact.given_name()?;
+    let surname = contact.surname()?;
+    let prefix = contact.title()?;
+    let suffix = contact.generational_abbreviation()?;
+    let email_addresses = contact.email_addresses()?;
+
+    // FN is the only property a card must have
+    let full_name = contact
+        .display_name()?
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| {
+            let name = given_name.iter().chain(&surname).join(" ");
+            (!name.is_empty()).then_some(name)
+        })
+        .or(contact.file_under()?)
+        .or_else(|| email_addresses.first().cloned())
+        .unwrap_or_else(|| "Unnamed".to_string());
+    card.text("FN", &full_name);
+
+    if [&surname, &given_name, &prefix, &suffix]
+        .iter()
+        .any(|part| part.is_some())
+    {
+        let name = [surname, given_name, None, prefix, suffix];
+        card.raw("N", &components(&name));
+    }
+    card.opt_text("NICKNAME", contact.nickname()?.as_deref());
+
+    for (index, email_address) in email_addresses.iter().enumerate() {
+        match index {
+            0 => card.text("EMAIL;PREF=1", email_address),
+            _ => card.text("EMAIL", email_address),
+        }
+    }
+
+    let phone_numbers = [
+        ("TEL;PREF=1;TYPE=voice", contact.primary_phone_number()?),
+        ("TEL;TYPE=work,voice", contact.business_phone_number()?),
+        ("TEL;TYPE=work,voice", contact.business_phone_number2()?),
+        ("TEL;TYPE=home,voice", contact.home_phone_number()?),
+        ("TEL;TYPE=cell", contact.mobile_phone_number()?),
+        ("TEL;TYPE=work,fax", contact.business_fax_number()?),
+        ("TEL", contact.callback_phone_number()?),
+    ];
+    for (name, number) in phone_numbers {
+        card.opt_text(name, number.as_deref());
+    }
+
+    let company_name = contact.company_name()?;
+    let department_name = contact.department_name()?;
+    if company_name.is_some() || department_name.is_some() {
+        card.raw("ORG", &components(&[company_name, department_name]));
+    }
+    card.opt_text("TITLE", contact.job_title()?.as_deref());
+
+    // the structured address is the business address, fall back to the
+    // free form postal address if there is none
+    let mut address = [
+        None,
+        None,
+        contact.street_address()?,
+        contact.locality()?,
+        contact.state_or_province()?,
+        contact.postal_code()?,
+        contact.country()?,
+    ];
+    if address.iter().all(|part| part.is_none()) {
+        address[2] = contact.postal_address()?;
+    }
+    if address.iter().any(|part| part.is_some()) {
+        card.raw("ADR;TYPE=work", &components(&address));
+    }
+
+    if let Some(birthday) = contact.birthday()? {
+        card.raw("BDAY", &birthday.format("%Y%m%d").to_string());
+    }
+    // URLs are URI values rather than text so they aren't escaped
+    if let Some(url) = contact.business_home_page()? {
+        card.raw("URL", url.trim());
+    }
+    card.opt_text("NOTE", contact.notes()?.as_deref());
+    if let Some(time) = contact.modification_time()? {
+        card.time("REV", time);
+    }
+
+    card.raw("END", "VCARD");
+
+    Ok(card.into_string())
+}
+
+/// Renders a distribution list as a vCard 4.0 group card. Members that are
+/// contacts or lists in the same file refer to their cards, all others are
+/// listed by email address.
+fn to_group_vcard(pff: &PffOpen, list: &DistributionList) -> Result<String> {
+    let mut card = ContentLines::new();
+    card.raw("BEGIN", "VCARD");
+    card.raw("VERSION", "4.0");
+    card.raw("UID", &uid(list.id()?));
+    card.raw("KIND", "group");
+
+    let name = list
+        .name()?
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "Unnamed".to_string());
+    card.text("FN", &name);
+
+    for member in list.members()? {
+        if let Some(member) = group_member(pff, &member)? {
+            card.raw("MEMBER", &member);
+        }
+    }
+
+    card.opt_text("NOTE", list.notes()?.as_deref());
+    if let Some(time) = list.modification_time()? {
+        card.time("REV", time);
+    }
+
+    card.raw("END", "VCARD");
+
+    Ok(card.into_string())
+}
+
+/// Returns the URI a group card uses to refer to `member`.
+fn group_member(pff: &PffOpen, member: &DistributionListMember) -> Result<Option<String>> {
+    if let Some(item) = member.resolve(pff)? {
+        if let item @ (AnyItem::Contact(_) | AnyItem::DistributionList(_)) = item.classify()? {
+            return Ok(Some(uid(item.id()?)));
+        }
+    }
+
+    Ok(member
+        .email_address()
+        .filter(|address| address.contains('@'))
+        .map(|address| format!("mailto:{address}")))
+}
+
+/// The UID of the card of the item with `id`, which lets group cards refer
+/// to the cards of their members.
+fn uid(id: u32) -> String {
+    format!("urn:pff-cli:{id}")
+}
+
+/// Joins the components of a structured value such as N or ADR.
+fn components(parts: &[Option<String>]) -> String {
+    parts
+        .iter()
+        .map(|part| {
+            part.as_deref()
+                .map(|part| content_lines::escape(part.trim()))
+                .unwrap_or_default()
+        })
+        .join(";")
+}
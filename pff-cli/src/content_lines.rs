@@ -0,0 +1,80 @@
+use chrono::NaiveDateTime;
+
+/// Longest line we write, in octets excluding the CRLF, as recommended by
+/// RFC 5545 and RFC 6350.
+const MAX_LINE_LEN: usize = 75;
+
+/// Builds the "content lines" that vCard and iCalendar files are made of.
+/// Long lines are folded and every line ends in CRLF.
+#[derive(Default)]
+pub(crate) struct ContentLines {
+    out: String,
+}
+
+impl ContentLines {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes `name:value` where `value` is already in the format the
+    /// property expects, for e.g. a date or a list of escaped components.
+    pub(crate) fn raw(&mut self, name: &str, value: &str) {
+        let line = format!("{name}:{value}");
+        let mut len = 0;
+
+        for c in line.chars() {
+            // fold before the line gets too long, taking care not to split a
+            // UTF-8 sequence
+            if len + c.len_utf8() > MAX_LINE_LEN {
+                self.out.push_str("\r\n ");
+                len = 1;
+            }
+            self.out.push(c);
+            len += c.len_utf8();
+        }
+        self.out.push_str("\r\n");
+    }
+
+    /// Writes a text property, escaping `value`.
+    pub(crate) fn text(&mut self, name: &str, value: &str) {
+        self.raw(name, &escape(value));
+    }
+
+    /// Writes a text property if there is a non empty value.
+    pub(crate) fn opt_text(&mut self, name: &str, value: Option<&str>) {
+        if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+            self.text(name, value);
+        }
+    }
+
+    /// Writes a UTC date-time property, for e.g. `20220301T100000Z`.
+    pub(crate) fn time(&mut self, name: &str, time: NaiveDateTime) {
+        self.raw(name, &format_time(time));
+    }
+
+    pub(crate) fn into_string(self) -> String {
+        self.out
+    }
+}
+
+/// Formats `time` as a UTC date-time, for e.g. `20220301T100000Z`.
+pub(crate) fn format_time(time: NaiveDateTime) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes the characters that have a meaning in text values.
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.replace("\r\n", "\n").chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ',' => escaped.push_str("\\,"),
+            ';' => escaped.push_str("\\;"),
+            '\n' | '\r' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use pff::{
+    item::{EntryType, ItemExt},
+    propertyvalue::PropertyValue,
+    recordset::RecordEntry,
+    FileOpenFlags, Pff,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::export::find_item;
+
+#[derive(ValueEnum, Clone, Copy, PartialOrd, Ord, Eq, Debug, PartialEq)]
+pub(crate) enum OutputFormat {
+    Json,
+    Table,
+}
+
+#[derive(Serialize, Debug)]
+struct Property {
+    record_set: usize,
+    tag: String,
+    value_type: Option<String>,
+    name: Option<String>,
+    value: Option<Value>,
+    error: Option<String>,
+}
+
+pub(crate) async fn run(pff_file: PathBuf, id: String, format: OutputFormat) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    let (_, item) =
+        find_item(&pff, &id)?.ok_or_else(|| anyhow!("Item was not found in the file."))?;
+
+    let mut properties = vec![];
+    for (index, record_set) in item.record_sets()?.enumerate() {
+        for entry in record_set?.entries()? {
+            properties.push(to_property(index, entry?)?);
+        }
+    }
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string(&properties)?),
+        OutputFormat::Table => print_table(&properties),
+    }
+
+    Ok(())
+}
+
+fn to_property(record_set: usize, entry: RecordEntry) -> Result<Property> {
+    let entry_type = entry.entry_type_id()?;
+    let value_type = entry.value_type();
+
+    // named properties are resolved through the name-to-ID map first since
+    // the entry type alone is ambiguous for those. A broken map shouldn't
+    // stop us from dumping the rest of the item.
+    let name = match entry.named_property() {
+        Ok(Some(named)) => Some(named.to_string()),
+        Ok(None) => match EntryType::from(entry_type) {
+            EntryType::Unknown => None,
+            known => Some(format!("{known:?}")),
+        },
+        Err(err) => Some(format!("<error: {err}>")),
+    };
+
+    let (value, error) = match entry.value() {
+        Ok(value) => (Some(to_json(&value)), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+
+    Ok(Property {
+        record_set,
+        tag: match value_type.as_ref() {
+            Ok(vt) => format!("{:#010x}", (entry_type << 16) | u32::from(*vt)),
+            Err(_) => format!("{:#06x}????", entry_type),
+        },
+        value_type: value_type.ok().map(|vt| format!("{vt:?}")),
+        name,
+        value,
+        error,
+    })
+}
+
+fn to_json(value: &PropertyValue) -> Value {
+    match value {
+        PropertyValue::Null => Value::Null,
+        PropertyValue::Integer16BitSigned(v) => json!(v),
+        PropertyValue::Integer32BitSigned(v) => json!(v),
+        PropertyValue::Float32Bit(v) => json!(v),
+        PropertyValue::Double64Bit(v) => json!(v),
+        PropertyValue::Currency(v) => json!(*v as f64 / 10_000.0),
+        PropertyValue::Error(v) => json!(format!("{v:#010x}")),
+        PropertyValue::Boolean(v) => json!(v),
+        PropertyValue::Integer64BitSigned(v) => json!(v),
+        PropertyValue::StringAscii(v) | PropertyValue::StringUnicode(v) => json!(v),
+        PropertyValue::Floatingtime(v) | PropertyValue::Filetime(v) => json!(v),
+        PropertyValue::Guid(v) => json!(v.to_string()),
+        PropertyValue::Unspecified(v)
+        | PropertyValue::Object(v)
+        | PropertyValue::ServerIdentifier(v)
+        | PropertyValue::Restriction(v)
+        | PropertyValue::RuleAction(v)
+        | PropertyValue::BinaryData(v) => json!(to_hex(v)),
+        PropertyValue::MultiValueInteger16BitSigned(v) => json!(v),
+        PropertyValue::MultiValueInteger32BitSigned(v) => json!(v),
+        PropertyValue::MultiValueFloat32Bit(v) => json!(v),
+        PropertyValue::MultiValueDouble64Bit(v) => json!(v),
+        PropertyValue::MultiValueCurrency(v) => {
+            json!(v.iter().map(|v| *v as f64 / 10_000.0).collect::<Vec<_>>())
+        }
+        PropertyValue::MultiValueInteger64BitSigned(v) => json!(v),
+        PropertyValue::MultiValueStringAscii(v) | PropertyValue::MultiValueStringUnicode(v) => {
+            json!(v)
+        }
+        PropertyValue::MultiValueFloatingtime(v) | PropertyValue::MultiValueFiletime(v) => {
+            json!(v)
+        }
+        PropertyValue::MultiValueGuid(v) => {
+            json!(v.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+        }
+        PropertyValue::MultiValueBinaryData(v) => {
+            json!(v.iter().map(|v| to_hex(v)).collect::<Vec<_>>())
+        }
+    }
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn print_table(properties: &[Property]) {
+    // values can be arbitrarily long (message bodies for instance) so we
+    // clip them to keep the table readable
+    const MAX_VALUE_LEN: usize = 80;
+
+    println!(
+        "{:<4} {:<10} {:<28} {:<48} VALUE",
+        "SET", "TAG", "TYPE", "NAME"
+    );
+    for property in properties {
+        let value = match (&property.value, &property.error) {
+            (Some(Value::String(s)), _) => s.clone(),
+            (Some(value), _) => value.to_string(),
+            (None, Some(err)) => format!("<error: {err}>"),
+            (None, None) => String::new(),
+        };
+        let value = value.replace(['\r', '\n'], " ");
+        let value = match value.char_indices().nth(MAX_VALUE_LEN) {
+            Some((index, _)) => format!("{}...", &value[..index]),
+            None => value,
+        };
+
+        println!(
+            "{:<4} {:<10} {:<28} {:<48} {}",
+            property.record_set,
+            property.tag,
+            property.value_type.as_deref().unwrap_or("?"),
+            property.name.as_deref().unwrap_or(""),
+            value
+        );
+    }
+}
@@ -1,53 +1,60 @@
-use std::{env, path::PathBuf};
-use std::{fs, str};
+use std::{
+    collections::HashSet,
+    env,
+    io::{self, Write},
+    path::PathBuf,
+};
+use std::{
+    fs::{self, File},
+    str,
+};
 
 use anyhow::Result;
-use pff::{item::ItemExt, message::Message as PffMessage, FileOpenFlags, Pff};
+use clap::ValueEnum;
+use pff::{
+    error::Error, item::PffItem, message::Message as PffMessage, path::ItemIdPath, FileOpenFlags,
+    Pff, PffOpen,
+};
 
-use crate::index::to_message;
+use crate::{index::to_message, names};
+
+#[derive(ValueEnum, Clone, Copy, PartialOrd, Ord, Eq, Debug, PartialEq)]
+pub(crate) enum ExportFormat {
+    /// The message summary as JSON
+    Json,
+    /// The full message as an RFC 5322 .eml file
+    Eml,
+}
 
 pub(crate) async fn run(
     pff_file: PathBuf,
     save_attachments: bool,
     attachment_save_to: Option<PathBuf>,
     id: String,
+    format: ExportFormat,
 ) -> Result<()> {
-    // parse the id path to the message
-    let id_path = id
-        .split('_')
-        .map(str::parse::<u32>)
-        .collect::<Vec<Result<_, _>>>()
-        .into_iter()
-        .collect::<Result<Vec<_>, _>>()?;
-
     // open pst/ost file
     let pff = Pff::new()?;
-    let pff = pff.open(
-        pff_file.as_path().to_str().expect("Path must be valid"),
-        FileOpenFlags::READ,
-    )?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
 
     // navigate the tree to get to the message
-    let mut item_ref = pff.root_folder()?;
-    let mut index = 0;
-    let mut message_id = 0;
-    while let (Some(item), true) = (item_ref.as_ref(), index < id_path.len()) {
-        message_id = id_path[index];
-        item_ref = item.sub_item_by_id(message_id)?;
-        index += 1;
-    }
-
-    if let Some(item) = item_ref {
+    if let Some((id_path, item)) = find_item(&pff, &id)? {
+        let message_id = id_path.id().unwrap_or_default();
         let message: PffMessage = item.into();
 
         if save_attachments && message.has_attachments()? {
             save_all_attachments(&message, attachment_save_to)?;
         }
 
-        let message = to_message(message_id.to_string(), true, message)?;
+        match format {
+            ExportFormat::Json => {
+                let message = to_message(message_id.to_string(), true, message)?;
 
-        // print JSON representation of the message
-        println!("{}", serde_json::to_string(&message)?);
+                // print JSON representation of the message
+                println!("{}", serde_json::to_string(&message)?);
+            }
+            ExportFormat::Eml => io::stdout().write_all(&message.to_mime()?)?,
+        }
     } else {
         eprintln!("Message was not found in the file.");
     }
@@ -55,18 +62,51 @@ pub(crate) async fn run(
     Ok(())
 }
 
+/// Finds the item identified by `id`, which is either an ID path such as
+/// "8354_8514_8546_7029316" or a display path such as "/Inbox/7029316".
+pub(crate) fn find_item(pff: &PffOpen, id: &str) -> Result<Option<(ItemIdPath, PffItem)>> {
+    let id_path = match id.starts_with('/') {
+        true => ItemIdPath::from_display_path(pff, id),
+        false => id.parse(),
+    };
+
+    match id_path.and_then(|id_path| Ok((id_path.resolve(pff)?, id_path))) {
+        Ok((item, id_path)) => Ok(Some((id_path, item))),
+        Err(Error::ItemNotFound(_)) => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
 fn save_all_attachments(message: &PffMessage, save_to: Option<PathBuf>) -> Result<()> {
     let attachments = message.attachments()?;
     let save_to = save_to.unwrap_or(env::current_dir()?);
+    let mut used_names = HashSet::new();
 
     for (index, attachment) in attachments.enumerate() {
         let attachment = attachment?;
-        let data = attachment.as_buffer()?;
-        let name = attachment
-            .display_name()?
-            .unwrap_or_else(|| format!("attachment_{}", index + 1));
-        let save_path = save_to.as_path().join(name);
-        fs::write(save_path, &data)?;
+        let embedded = attachment.embedded_message()?;
+
+        // attached messages are saved as .eml files including their own
+        // attachments
+        let name = attachment.file_name()?.and_then(|name| match embedded {
+            Some(_) => names::sanitize(&format!("{name}.eml")),
+            None => names::sanitize(&name),
+        });
+        let name = name.unwrap_or_else(|| match embedded {
+            Some(_) => format!("attachment_{}.eml", index + 1),
+            None => format!("attachment_{}", index + 1),
+        });
+        let save_path = save_to.join(names::unique(name, &save_to, &mut used_names));
+
+        match embedded {
+            Some(embedded) => fs::write(save_path, embedded.to_mime()?)?,
+            None => {
+                // stream the data so that large attachments aren't loaded
+                // into memory
+                let mut file = File::create(save_path)?;
+                io::copy(&mut attachment.reader()?, &mut file)?;
+            }
+        }
     }
 
     Ok(())
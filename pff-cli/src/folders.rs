@@ -0,0 +1,67 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use pff::{folder::Folder, item::ItemExt};
+
+use crate::mbox;
+
+/// Calls `export` for `folder` and every folder below it along with the
+/// path of the file the folder's items should be written to. The layout is
+/// the same as for `export-mbox`: the root folder's items go to
+/// `<dir>/Root.<extension>` and each sub folder's items to
+/// `<dir>/<name>.<extension>` with its sub folders under `<dir>/<name>/`.
+pub(crate) fn for_each_folder<F>(
+    folder: &Folder,
+    dir: &Path,
+    extension: &str,
+    export: &mut F,
+) -> Result<()>
+where
+    F: FnMut(&Folder, PathBuf) -> Result<()>,
+{
+    export(folder, dir.join(format!("Root.{extension}")))?;
+
+    // a top level folder called "Root" mustn't end up in the root folder's
+    // file
+    let names = HashSet::from(["root".to_string()]);
+    for_each_sub_folder(folder, dir, extension, names, export)
+}
+
+/// Calls `export` for the sub folders of `folder` and every folder below
+/// them. `names` has the lower case names that are already taken in `dir`.
+fn for_each_sub_folder<F>(
+    folder: &Folder,
+    dir: &Path,
+    extension: &str,
+    mut names: HashSet<String>,
+    export: &mut F,
+) -> Result<()>
+where
+    F: FnMut(&Folder, PathBuf) -> Result<()>,
+{
+    for sub_folder in folder.sub_folders()? {
+        let sub_folder = sub_folder?;
+        let id = sub_folder.id()?;
+
+        // sibling folders can share a name so make them unique
+        let mut name = mbox::folder_name(sub_folder.name()?.as_deref(), id);
+        if !names.insert(name.to_lowercase()) {
+            name = format!("{name} ({id})");
+            names.insert(name.to_lowercase());
+        }
+
+        export(&sub_folder, dir.join(format!("{name}.{extension}")))?;
+        for_each_sub_folder(
+            &sub_folder,
+            &dir.join(&name),
+            extension,
+            HashSet::new(),
+            export,
+        )?;
+    }
+
+    Ok(())
+}
@@ -8,12 +8,8 @@ use chrono::NaiveDateTime;
 use itertools::Itertools;
 use meilisearch_sdk::{client::Client, indexes::Index};
 use pff::{
-    folder::Folder,
-    item::{Item, ItemExt, ItemType},
-    message::Message as PffMessage,
-    message::MessageBodyType,
-    recipients::Recipient,
-    FileOpenFlags, Pff,
+    message::Message as PffMessage, message::MessageBodyType, path::ItemIdPath,
+    recipients::Recipient, threads::ThreadInfo, walk::Walker, FileOpenFlags, Pff,
 };
 use serde::{Deserialize, Serialize};
 use tokio::{sync::mpsc, task::JoinHandle};
@@ -33,17 +29,27 @@ pub(crate) struct IndexParams {
 pub(crate) struct Agent {
     pub(crate) name: Option<String>,
     pub(crate) email: Option<String>,
+    /// How a recipient was addressed: "to", "cc" or "bcc"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) kind: Option<String>,
 }
 
 impl Agent {
     fn new(name: Option<String>, email: Option<String>) -> Self {
-        Self { name, email }
+        Self {
+            name,
+            email,
+            kind: None,
+        }
     }
 }
 
 impl From<Recipient> for Agent {
     fn from(recipient: Recipient) -> Self {
-        Self::new(recipient.display_name, recipient.email_address)
+        Self {
+            kind: recipient.kind.map(|kind| kind.to_string()),
+            ..Self::new(recipient.display_name, recipient.email_address)
+        }
     }
 }
 
@@ -74,6 +80,24 @@ pub(crate) struct Message {
     pub(crate) delivery_time: Option<NaiveDateTime>,
     pub(crate) has_attachments: bool,
     pub(crate) attachments: Option<Vec<String>>,
+    /// Messages in the same conversation share this ID
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    /// The Internet Message-ID, without angle brackets, for deduplication
+    #[serde(default)]
+    pub(crate) message_id: Option<String>,
+    #[serde(default)]
+    pub(crate) in_reply_to: Option<String>,
+    #[serde(default)]
+    pub(crate) references: Vec<String>,
+    #[serde(default)]
+    pub(crate) list_id: Option<String>,
+    /// The Received header fields, latest hop first
+    #[serde(default)]
+    pub(crate) received: Vec<String>,
+    /// Messages attached to this one, for e.g. forwarded mail
+    #[serde(default)]
+    pub(crate) embedded_messages: Vec<Message>,
 }
 
 async fn index_messages(
@@ -140,67 +164,45 @@ fn message_task(
 ) -> Result<()> {
     // open pst/ost file
     let pff = Pff::new()?;
-    let pff = pff.open(
-        pff_file.as_path().to_str().expect("Path must be valid"),
-        FileOpenFlags::READ,
-    )?;
-
-    if let Some(root_folder) = pff.root_folder()? {
-        let mut id_path = vec![];
-        enum_items(root_folder, include_body, &mut id_path, tracker, &tx)?;
-    }
-
-    Ok(())
-}
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    for entry in Walker::new(&pff).continue_on_error(true) {
+        let (id, message) = match entry {
+            // everything but folders is indexed as a message
+            Ok((_, id_path, item)) => match item.into_message() {
+                Some(message) => (index_id(&id_path), Some(message)),
+                None => continue,
+            },
+            // items that can't be read are recorded as failed, the same as
+            // messages that fail to load
+            Err(error) if error.item_id.is_some() => (index_id(&error.id_path), None),
+            // folders, and items whose ID is unknown, have nothing to record
+            // them under
+            Err(error) => {
+                eprintln!("Failed to index {error}");
+                continue;
+            }
+        };
 
-fn enum_items<T>(
-    root: T,
-    include_body: bool,
-    id_path: &mut Vec<u32>,
-    tracker: ProgressTracker,
-    tx: &mpsc::Sender<(String, Option<Message>)>,
-) -> Result<()>
-where
-    T: Item,
-{
-    if root.type_()? == ItemType::Folder {
-        let folder = root.into_folder()?;
-
-        enum_messages(&folder, include_body, id_path, tracker.clone(), tx)?;
-
-        for item in folder.sub_folders()? {
-            let item = item?;
-            id_path.push(item.id()?);
-            enum_items(item, include_body, id_path, tracker.clone(), tx)?;
-            id_path.pop();
+        // skip messages that are already indexed/faulted
+        if !tracker.contains_message(&id) {
+            let message =
+                message.and_then(|message| to_message(id.clone(), include_body, message).ok());
+            tx.blocking_send((id, message))?;
         }
     }
 
     Ok(())
 }
 
-fn enum_messages(
-    folder: &Folder,
-    include_body: bool,
-    id_path: &[u32],
-    tracker: ProgressTracker,
-    tx: &mpsc::Sender<(String, Option<Message>)>,
-) -> Result<()> {
-    for message in folder.messages()? {
-        let message = message?;
-        let id = format!(
-            "{}_{}",
-            id_path.iter().map(|id| id.to_string()).join("_"),
-            message.id()?
-        );
-
-        // skip messages that are already indexed/faulted
-        if !tracker.contains_message(&id) {
-            tx.blocking_send((id.clone(), to_message(id, include_body, message).ok()))?;
-        }
+/// The ID a message is indexed under, i.e., its ID path. Messages in the
+/// root folder get a leading `_` like they did before so that existing
+/// progress files and indexes still match.
+fn index_id(id_path: &ItemIdPath) -> String {
+    match id_path.ids() {
+        [id] => format!("_{id}"),
+        _ => id_path.to_string(),
     }
-
-    Ok(())
 }
 
 pub(crate) fn to_message(id: String, include_body: bool, message: PffMessage) -> Result<Message> {
@@ -219,7 +221,10 @@ pub(crate) fn to_message(id: String, include_body: bool, message: PffMessage) ->
     let send_time = message.client_submit_time()?;
     let delivery_time = message.delivery_time()?;
     let has_attachments = message.has_attachments()?;
+    let headers = message.headers()?.unwrap_or_default();
+    let thread_info = ThreadInfo::from_message(&message)?;
     let mut attachments = None;
+    let mut embedded_messages = vec![];
     if has_attachments {
         attachments = Some(
             message
@@ -227,7 +232,7 @@ pub(crate) fn to_message(id: String, include_body: bool, message: PffMessage) ->
                 .enumerate()
                 .map(|(index, att)| {
                     att.and_then(|att| {
-                        att.display_name()
+                        att.file_name()
                             .map(|dn| dn.unwrap_or_else(|| format!("attachment_{}", index + 1)))
                     })
                 })
@@ -235,6 +240,15 @@ pub(crate) fn to_message(id: String, include_body: bool, message: PffMessage) ->
                 .into_iter()
                 .collect::<Result<Vec<_>, _>>()?,
         );
+
+        // index forwarded messages along with the message they are attached
+        // to so that their content is searchable too
+        for (index, attachment) in message.attachments()?.enumerate() {
+            if let Some(embedded) = attachment?.embedded_message()? {
+                let embedded_id = format!("{id}-{}", index + 1);
+                embedded_messages.push(to_message(embedded_id, include_body, embedded)?);
+            }
+        }
     }
 
     Ok(Message {
@@ -247,6 +261,13 @@ pub(crate) fn to_message(id: String, include_body: bool, message: PffMessage) ->
         delivery_time,
         has_attachments,
         attachments,
+        thread_id: thread_info.thread_id(),
+        message_id: thread_info.message_id,
+        in_reply_to: thread_info.in_reply_to,
+        references: thread_info.references,
+        list_id: headers.list_id().map(str::to_string),
+        received: headers.received().into_iter().map(str::to_string).collect(),
+        embedded_messages,
     })
 }
 
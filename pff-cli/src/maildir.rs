@@ -0,0 +1,207 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
+use itertools::Itertools;
+use pff::{
+    error::Error,
+    folder::Folder,
+    item::ItemExt,
+    message::{FlagStatus, Message, MessageFlags, MessageStatus},
+    FileOpenFlags, Pff,
+};
+
+use crate::mbox;
+
+/// Host name part of the generated Maildir file names
+const HOST_NAME: &str = "pff-cli";
+
+struct Exporter {
+    dest: PathBuf,
+    exported: usize,
+    failed: usize,
+}
+
+pub(crate) async fn run(pff_file: PathBuf, dest: PathBuf) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    let mut exporter = Exporter {
+        dest,
+        exported: 0,
+        failed: 0,
+    };
+
+    if let Some(root_folder) = pff.root_folder()? {
+        let root_folder = root_folder.into_folder()?;
+
+        // the root folder becomes the top level Maildir (the INBOX as far as
+        // Maildir++ is concerned) and every other folder a Maildir++ sub
+        // folder of it
+        let root_dir = exporter.dest.clone();
+        exporter.export_messages(&root_folder, &root_dir, &[])?;
+        exporter.export_sub_folders(&root_folder, "", &mut vec![])?;
+    }
+
+    println!(
+        "\nDone. Exported {} messages, {} failed.",
+        exporter.exported, exporter.failed
+    );
+
+    Ok(())
+}
+
+impl Exporter {
+    /// Creates a Maildir++ folder for each sub folder of `folder`. Maildir++
+    /// keeps all folders at the top level and uses the `.` delimited folder
+    /// path as the directory name, for e.g. `.Inbox.Projects`.
+    fn export_sub_folders(
+        &mut self,
+        folder: &Folder,
+        prefix: &str,
+        id_path: &mut Vec<u32>,
+    ) -> Result<()> {
+        let mut names = HashSet::new();
+
+        for sub_folder in folder.sub_folders()? {
+            let sub_folder = sub_folder?;
+            let id = sub_folder.id()?;
+
+            // sibling folders can share a name so make them unique
+            let mut name = folder_name(sub_folder.name()?.as_deref(), id);
+            if !names.insert(name.to_lowercase()) {
+                name = format!("{name} ({id})");
+                names.insert(name.to_lowercase());
+            }
+
+            let prefix = format!("{prefix}.{}", encode_mailbox_name(&name));
+            let dir = self.dest.join(&prefix);
+
+            id_path.push(id);
+            self.export_messages(&sub_folder, &dir, id_path)?;
+
+            // marks the directory as a Maildir++ folder
+            fs::File::create(dir.join("maildirfolder"))?;
+
+            self.export_sub_folders(&sub_folder, &prefix, id_path)?;
+            id_path.pop();
+        }
+
+        Ok(())
+    }
+
+    fn export_messages(&mut self, folder: &Folder, dir: &Path, id_path: &[u32]) -> Result<()> {
+        for sub_dir in ["cur", "new", "tmp"] {
+            fs::create_dir_all(dir.join(sub_dir))?;
+        }
+
+        for message in folder.messages()? {
+            let message = message?;
+            let id = id_path
+                .iter()
+                .copied()
+                .chain([message.id()?])
+                .map(|id| id.to_string())
+                .join("_");
+
+            match write_message(&message, dir, &id) {
+                Ok(_) => self.exported += 1,
+                Err(err) => {
+                    eprintln!("Failed to export message {id}: {err}");
+                    self.failed += 1;
+                }
+            }
+
+            print!("Exported {} messages\r", self.exported);
+            io::stdout().flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the message to `tmp` and then moves it to `cur` as the Maildir
+/// spec requires. The file name is derived from the message's ID path so
+/// running the export again replaces the messages instead of duplicating
+/// them.
+fn write_message(message: &Message, dir: &Path, id: &str) -> Result<()> {
+    let time = message
+        .delivery_time()?
+        .or(message.client_submit_time()?)
+        .map(|time| time.and_utc().timestamp())
+        .unwrap_or_default();
+    // a follow up flag we don't know is no reason to lose the message
+    let flag_status = match message.flag_status() {
+        Ok(flag_status) => Some(flag_status),
+        Err(Error::BadFlagStatus(_)) => None,
+        Err(error) => return Err(error.into()),
+    };
+    let flags = maildir_flags(message.flags()?, message.status()?, flag_status);
+
+    let unique = format!("{time}.P{id}.{HOST_NAME}");
+    let tmp_path = dir.join("tmp").join(&unique);
+    let cur_path = dir.join("cur").join(format!("{unique}:2,{flags}"));
+
+    fs::write(&tmp_path, message.to_mime()?)?;
+    fs::rename(&tmp_path, &cur_path)?;
+
+    Ok(())
+}
+
+/// Maps the message state to Maildir info flags. The flags must be listed in
+/// ASCII order.
+fn maildir_flags(
+    flags: MessageFlags,
+    status: MessageStatus,
+    flag_status: Option<FlagStatus>,
+) -> String {
+    [
+        ('D', flags.contains(MessageFlags::UNSENT)),
+        ('F', flag_status == Some(FlagStatus::Flagged)),
+        ('R', status.contains(MessageStatus::ANSWERED)),
+        ('S', flags.contains(MessageFlags::READ)),
+        ('T', status.contains(MessageStatus::DELETE_MARKED)),
+    ]
+    .into_iter()
+    .filter_map(|(flag, set)| set.then_some(flag))
+    .collect()
+}
+
+/// The `.` separates folders in Maildir++ so it can't appear in a name.
+fn folder_name(name: Option<&str>, id: u32) -> String {
+    mbox::folder_name(name, id).replace('.', "_")
+}
+
+/// Encodes a folder name in the modified UTF-7 used for IMAP mailbox names
+/// (RFC 3501 section 5.1.3) which is what Dovecot expects on disk.
+fn encode_mailbox_name(name: &str) -> String {
+    let mut encoded = String::new();
+
+    for (printable, chars) in &name.chars().chunk_by(|c| (' '..='~').contains(c)) {
+        if printable {
+            for c in chars {
+                match c {
+                    '&' => encoded.push_str("&-"),
+                    c => encoded.push(c),
+                }
+            }
+        } else {
+            let utf16 = chars
+                .collect::<String>()
+                .encode_utf16()
+                .flat_map(u16::to_be_bytes)
+                .collect::<Vec<_>>();
+            encoded.push('&');
+            encoded.push_str(&STANDARD_NO_PAD.encode(utf16).replace('/', ","));
+            encoded.push('-');
+        }
+    }
+
+    encoded
+}
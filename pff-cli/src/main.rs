@@ -3,10 +3,21 @@ use std::path::PathBuf;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
+mod calendar;
+mod contacts;
+mod content_lines;
+mod dump;
 mod export;
+mod folders;
 mod index;
+mod maildir;
+mod mbox;
+mod names;
 mod progress;
 mod search;
+mod tasks;
+mod threads;
+mod tree;
 
 #[derive(Parser, Debug)]
 #[clap(version)]
@@ -22,7 +33,7 @@ struct Opts {
 
 #[derive(Subcommand, PartialOrd, Ord, Eq, Debug, PartialEq)]
 pub(crate) enum Command {
-    /// Export a single message as JSON
+    /// Export a single message as JSON or as an .eml file
     ExportMessage {
         #[clap(long, short)]
         /// The ID of the message to export. The ID must be given as
@@ -30,7 +41,8 @@ pub(crate) enum Command {
         /// This ID can be fetched from the Meilisearch server search results.
         /// Note that this message ID path must not include the root folder's ID
         /// which is what you get by default if you indexed your emails using the
-        /// `pff-cli index` command.
+        /// `pff-cli index` command. The message can also be given by its folder
+        /// path followed by its ID, for example /Inbox/Projects/7029316.
         id: String,
 
         /// Should attachments (if any) be saved to the file system
@@ -42,6 +54,43 @@ pub(crate) enum Command {
         /// specified then the path defaults to the current folder.
         #[clap(long, short)]
         attachment_save_to: Option<PathBuf>,
+
+        #[clap(long, short, value_enum, default_value = "json")]
+        /// Output format. 'eml' writes the full message including all its
+        /// attachments in a form that any mail client can open.
+        format: export::ExportFormat,
+    },
+
+    /// Dump every property stored on an item
+    DumpProperties {
+        #[clap(long, short)]
+        /// The ID path of the item to dump. This takes the same form as the ID
+        /// given to the `export-message` command.
+        id: String,
+
+        #[clap(long, short, value_enum, default_value = "table")]
+        /// Output format
+        format: dump::OutputFormat,
+    },
+
+    /// Print the folder hierarchy along with the ID path and item counts of
+    /// each folder
+    Tree {
+        #[clap(long, short, value_enum, default_value = "text")]
+        /// Output format
+        format: tree::OutputFormat,
+    },
+
+    /// Group all messages into conversations and print them along with the
+    /// ID path of each message, oldest first
+    Threads {
+        #[clap(long, short, value_enum, default_value = "text")]
+        /// Output format
+        format: threads::OutputFormat,
+
+        #[clap(long, short, default_value = "1")]
+        /// Only print threads with at least this many messages
+        min_messages: usize,
     },
 
     /// Index all emails to a Meilisearch server
@@ -67,6 +116,45 @@ pub(crate) enum Command {
         include_body: bool,
     },
 
+    /// Export all messages to mbox files, one per folder
+    ExportMbox {
+        /// Folder to write the mbox files to. The folder hierarchy of the
+        /// PST/OST file is recreated under this folder.
+        dest: PathBuf,
+
+        #[clap(long, short = 'f', default_value = "mbox-progress.csv")]
+        /// File to save progress to so we can resume later
+        progress_file: PathBuf,
+    },
+
+    /// Export all messages to a Maildir++ tree that mirrors the folders
+    ExportMaildir {
+        /// Folder to create the Maildir in. Each folder in the PST/OST file
+        /// becomes a Maildir++ sub folder and each message an .eml file.
+        dest: PathBuf,
+    },
+
+    /// Export all contacts as vCard 4.0 files, one per folder
+    ExportContacts {
+        /// Folder to write the .vcf files to. The folder hierarchy of the
+        /// PST/OST file is recreated under this folder.
+        dest: PathBuf,
+    },
+
+    /// Export all calendar items as iCalendar files, one per folder
+    ExportCalendar {
+        /// Folder to write the .ics files to. The folder hierarchy of the
+        /// PST/OST file is recreated under this folder.
+        dest: PathBuf,
+    },
+
+    /// Export all tasks as iCalendar files, one per folder
+    ExportTasks {
+        /// Folder to write the .ics files to. The folder hierarchy of the
+        /// PST/OST file is recreated under this folder.
+        dest: PathBuf,
+    },
+
     /// Search for messages and print result as JSON
     Search {
         #[clap(long, short)]
@@ -109,7 +197,17 @@ async fn main() -> Result<()> {
             id,
             save_attachments,
             attachment_save_to,
-        } => export::run(pff_file, save_attachments, attachment_save_to, id).await,
+            format,
+        } => export::run(pff_file, save_attachments, attachment_save_to, id, format).await,
+
+        Command::DumpProperties { id, format } => dump::run(pff_file, id, format).await,
+
+        Command::Tree { format } => tree::run(pff_file, format).await,
+
+        Command::Threads {
+            format,
+            min_messages,
+        } => threads::run(pff_file, format, min_messages).await,
 
         Command::Index {
             server,
@@ -129,6 +227,26 @@ async fn main() -> Result<()> {
             index::run(params).await
         }
 
+        Command::ExportMbox {
+            dest,
+            progress_file,
+        } => {
+            let params = mbox::MboxParams {
+                pff_file,
+                dest,
+                progress_file,
+            };
+            mbox::run(params).await
+        }
+
+        Command::ExportMaildir { dest } => maildir::run(pff_file, dest).await,
+
+        Command::ExportContacts { dest } => contacts::run(pff_file, dest).await,
+
+        Command::ExportCalendar { dest } => calendar::run(pff_file, dest).await,
+
+        Command::ExportTasks { dest } => tasks::run(pff_file, dest).await,
+
         Command::Search {
             server,
             api_key,
@@ -0,0 +1,233 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime};
+use itertools::Itertools;
+use pff::{folder::Folder, item::ItemExt, message::Message, FileOpenFlags, Pff};
+
+use crate::{
+    names,
+    progress::{IndexStatus, ProgressTracker},
+};
+
+pub(crate) struct MboxParams {
+    pub(crate) pff_file: PathBuf,
+    pub(crate) dest: PathBuf,
+    pub(crate) progress_file: PathBuf,
+}
+
+struct Exporter {
+    dest: PathBuf,
+    tracker: ProgressTracker,
+    progress_file: PathBuf,
+    exported: usize,
+    failed: usize,
+}
+
+pub(crate) async fn run(args: MboxParams) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&args.pff_file, FileOpenFlags::READ)?;
+
+    let mut exporter = Exporter {
+        dest: args.dest.clone(),
+        tracker: ProgressTracker::from_file(&args.progress_file)?,
+        progress_file: args.progress_file,
+        exported: 0,
+        failed: 0,
+    };
+
+    if let Some(root_folder) = pff.root_folder()? {
+        let root_folder = root_folder.into_folder()?;
+        fs::create_dir_all(&args.dest)?;
+
+        // messages stored directly in the root folder are rare but we don't
+        // want to lose them
+        exporter.export_messages(&root_folder, &args.dest.join("Root.mbox"), &[])?;
+        // a top level folder called "Root" mustn't end up in Root.mbox
+        let names = HashSet::from(["root".to_string()]);
+        exporter.export_sub_folders(&root_folder, &args.dest, names, &mut vec![])?;
+    }
+
+    exporter.tracker.to_file(&exporter.progress_file)?;
+
+    println!(
+        "\nDone. Exported {} messages, {} failed.",
+        exporter.exported, exporter.failed
+    );
+
+    Ok(())
+}
+
+impl Exporter {
+    /// Writes each sub folder of `folder` to `<dir>/<name>.mbox` and its sub
+    /// folders in turn to `<dir>/<name>/`. `names` has the lower case names
+    /// that are already taken in `dir`.
+    fn export_sub_folders(
+        &mut self,
+        folder: &Folder,
+        dir: &Path,
+        mut names: HashSet<String>,
+        id_path: &mut Vec<u32>,
+    ) -> Result<()> {
+        for sub_folder in folder.sub_folders()? {
+            let sub_folder = sub_folder?;
+            let id = sub_folder.id()?;
+
+            // sibling folders can share a name so make them unique
+            let mut name = folder_name(sub_folder.name()?.as_deref(), id);
+            if !names.insert(name.to_lowercase()) {
+                name = format!("{name} ({id})");
+                names.insert(name.to_lowercase());
+            }
+
+            id_path.push(id);
+            self.export_messages(&sub_folder, &dir.join(format!("{name}.mbox")), id_path)?;
+            self.export_sub_folders(&sub_folder, &dir.join(&name), HashSet::new(), id_path)?;
+            id_path.pop();
+        }
+
+        Ok(())
+    }
+
+    fn export_messages(&mut self, folder: &Folder, path: &Path, id_path: &[u32]) -> Result<()> {
+        if folder.messages_count()? == 0 {
+            return Ok(());
+        }
+
+        let mut out: Option<BufWriter<File>> = None;
+
+        for message in folder.messages()? {
+            let message = message?;
+            let id = id_path
+                .iter()
+                .copied()
+                .chain([message.id()?])
+                .map(|id| id.to_string())
+                .join("_");
+
+            // skip messages that were exported in a previous run
+            if self.tracker.contains_message(&id) {
+                continue;
+            }
+
+            let out = match out.as_mut() {
+                Some(out) => out,
+                None => out.insert(self.open_mbox(path)?),
+            };
+
+            match to_mbox_entry(&message) {
+                Ok(entry) => {
+                    out.write_all(&entry)?;
+                    self.tracker.add_message(id, IndexStatus::Indexed);
+                    self.exported += 1;
+                }
+                Err(_) => {
+                    self.tracker.add_message(id, IndexStatus::Failed);
+                    self.failed += 1;
+                }
+            }
+
+            print!("Exported {} messages\r", self.exported);
+            io::stdout().flush()?;
+        }
+
+        // save progress once the folder's mbox is safely on disk, along with
+        // its length so that a later run knows where this run's output ends
+        if let Some(out) = out {
+            let file = out.into_inner()?;
+            file.sync_all()?;
+            let len = file.metadata()?.len();
+            self.tracker.set_file_len(self.file_key(path), len);
+            self.tracker.to_file(&self.progress_file)?;
+        }
+
+        Ok(())
+    }
+
+    fn open_mbox(&self, path: &Path) -> Result<BufWriter<File>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // append so that resuming an export adds to the existing mbox, but
+        // first drop whatever an interrupted run wrote after the progress
+        // was last saved since those messages are exported again. Files
+        // this progress file knows nothing about are left alone.
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        if let Some(len) = self.tracker.file_len(&self.file_key(path)) {
+            if file.metadata()?.len() > len {
+                file.set_len(len)?;
+            }
+        }
+
+        Ok(BufWriter::new(file))
+    }
+
+    /// The key of an mbox file in the progress file, i.e., its path relative
+    /// to the destination folder so that it doesn't depend on how the
+    /// destination was given.
+    fn file_key(&self, path: &Path) -> String {
+        path.strip_prefix(&self.dest)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Turns a folder name into something that is safe to use as a file name,
+/// falling back to the folder's ID for folders without a usable name.
+pub(crate) fn folder_name(name: Option<&str>, id: u32) -> String {
+    name.and_then(names::sanitize)
+        .unwrap_or_else(|| id.to_string())
+}
+
+fn to_mbox_entry(message: &Message) -> Result<Vec<u8>> {
+    let sender = message
+        .sender_email_address()?
+        .map(|email| email.split_whitespace().join(""))
+        .filter(|email| !email.is_empty())
+        .unwrap_or_else(|| "MAILER-DAEMON".to_string());
+    let date = message
+        .client_submit_time()?
+        .or(message.delivery_time()?)
+        .unwrap_or(DateTime::UNIX_EPOCH.naive_utc());
+
+    let mut entry = from_line(&sender, date).into_bytes();
+    escape_message(&message.to_mime()?, &mut entry);
+
+    Ok(entry)
+}
+
+fn from_line(sender: &str, date: NaiveDateTime) -> String {
+    // the date in the separator line uses the asctime format
+    format!("From {sender} {}\n", date.format("%a %b %e %H:%M:%S %Y"))
+}
+
+/// Appends `data` to `out` using the "mboxrd" conventions: line endings are
+/// converted to LF and lines matching `>*From ` get an extra `>` so that
+/// they can't be mistaken for the start of the next message and can be
+/// unescaped again by readers.
+fn escape_message(data: &[u8], out: &mut Vec<u8>) {
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        let line = line
+            .strip_suffix(b"\n")
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .unwrap_or(line);
+
+        let quotes = line.iter().take_while(|&&b| b == b'>').count();
+        if line[quotes..].starts_with(b"From ") {
+            out.push(b'>');
+        }
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+
+    // messages are separated by an empty line
+    out.push(b'\n');
+}
@@ -0,0 +1,78 @@
+use std::{collections::HashSet, path::Path};
+
+/// Longest file name we generate, in bytes. Most file systems allow 255.
+const MAX_NAME_LEN: usize = 200;
+
+/// Device names that Windows doesn't allow as file names, with or without
+/// an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turns a name read from the PST/OST file into something that is safe to
+/// use as a file name on any platform. Returns `None` if nothing usable is
+/// left of the name.
+pub(crate) fn sanitize(name: &str) -> Option<String> {
+    let name = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+    let name = name.trim().trim_matches('.').trim();
+
+    if name.is_empty() {
+        return None;
+    }
+
+    let (stem, extension) = split_extension(name);
+    let stem = match RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+    {
+        true => format!("_{stem}"),
+        false => truncate(stem, MAX_NAME_LEN.saturating_sub(extension.len())).to_string(),
+    };
+
+    Some(format!("{stem}{extension}"))
+}
+
+/// Makes `name` unique among the names in `used` and the files already in
+/// `dir` by adding a counter before the extension, for e.g. `report (2).pdf`.
+/// Names are compared case insensitively since not all file systems are case
+/// sensitive.
+pub(crate) fn unique(name: String, dir: &Path, used: &mut HashSet<String>) -> String {
+    let is_free = |name: &str, used: &HashSet<String>| {
+        !used.contains(&name.to_lowercase()) && !dir.join(name).exists()
+    };
+
+    let mut unique_name = name.clone();
+    let mut counter = 2;
+    while !is_free(&unique_name, used) {
+        let (stem, extension) = split_extension(&name);
+        unique_name = format!("{stem} ({counter}){extension}");
+        counter += 1;
+    }
+
+    used.insert(unique_name.to_lowercase());
+    unique_name
+}
+
+/// Splits `name` into the stem and the extension including its leading dot.
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(index) if index > 0 && name.len() - index <= 16 => name.split_at(index),
+        _ => (name, ""),
+    }
+}
+
+fn truncate(value: &str, max_len: usize) -> &str {
+    let mut end = value.len().min(max_len);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
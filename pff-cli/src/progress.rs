@@ -10,32 +10,43 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum IndexStatus {
-    /// Message has been indexed
+    /// Message has been indexed (or exported)
     Indexed,
 
-    /// Message failed to load and won't be indexed
+    /// Message failed to load and won't be indexed (or exported)
     Failed,
 }
 
 #[derive(Clone)]
 pub(crate) struct ProgressTracker {
     pub messages: Arc<Mutex<BTreeMap<String, IndexStatus>>>,
+    /// How far each file that messages are exported into had been written
+    /// when the progress was saved.
+    pub files: Arc<Mutex<BTreeMap<String, u64>>>,
 }
 
 impl ProgressTracker {
     pub(crate) fn from_file(path: &Path) -> Result<Self> {
         let mut messages = BTreeMap::new();
+        let mut files = BTreeMap::new();
 
         if path.exists() {
             let mut rdr = ReaderBuilder::new().has_headers(false).from_path(path)?;
-            for result in rdr.deserialize() {
-                let (id, status) = result?;
-                messages.insert(id, status);
+            for result in rdr.records() {
+                // file rows have a length where message rows have a status
+                let record = result?;
+                if let Ok((id, status)) = record.deserialize(None) {
+                    messages.insert(id, status);
+                } else {
+                    let (file, len) = record.deserialize(None)?;
+                    files.insert(file, len);
+                }
             }
         }
 
         Ok(Self {
             messages: Arc::new(Mutex::new(messages)),
+            files: Arc::new(Mutex::new(files)),
         })
     }
 
@@ -52,11 +63,24 @@ impl ProgressTracker {
         self.messages.lock().unwrap().contains_key(id)
     }
 
+    /// Records that `file` holds everything exported into it so far once it
+    /// is `len` bytes long.
+    pub(crate) fn set_file_len(&mut self, file: String, len: u64) {
+        self.files.lock().unwrap().insert(file, len);
+    }
+
+    pub(crate) fn file_len(&self, file: &str) -> Option<u64> {
+        self.files.lock().unwrap().get(file).copied()
+    }
+
     pub(crate) fn to_file(&self, path: &Path) -> Result<()> {
         let mut wtr = Writer::from_path(path)?;
         for (id, status) in self.messages.lock().unwrap().iter() {
             wtr.serialize((id, status))?;
         }
+        for (file, len) in self.files.lock().unwrap().iter() {
+            wtr.serialize((file, len))?;
+        }
         Ok(())
     }
 }
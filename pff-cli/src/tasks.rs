@@ -0,0 +1,151 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use pff::{
+    item::{AnyItem, ItemExt},
+    recurrence::RecurrenceEnd,
+    task::{Task, TaskStatus},
+    FileOpenFlags, Pff,
+};
+
+use crate::{
+    calendar::{self, format_date},
+    content_lines::ContentLines,
+    folders,
+};
+
+pub(crate) async fn run(pff_file: PathBuf, dest: PathBuf) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    let mut exported = 0;
+    let mut failed = 0;
+
+    if let Some(root_folder) = pff.root_folder()? {
+        let root_folder = root_folder.into_folder()?;
+
+        // every folder that holds tasks gets its own .ics file with all of
+        // the folder's tasks in it
+        folders::for_each_folder(&root_folder, &dest, "ics", &mut |folder, path| {
+            let mut todos = String::new();
+
+            for message in folder.messages()? {
+                let AnyItem::Task(task) = message?.classify()? else {
+                    continue;
+                };
+
+                match to_vtodo(&task) {
+                    Ok(todo) => {
+                        todos.push_str(&todo);
+                        exported += 1;
+                    }
+                    Err(_) => failed += 1,
+                }
+
+                print!("Exported {exported} tasks\r");
+                io::stdout().flush()?;
+            }
+
+            if !todos.is_empty() {
+                calendar::write_calendar(&path, &todos)?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    println!("\nDone. Exported {exported} tasks, {failed} failed.");
+
+    Ok(())
+}
+
+/// Renders a task as a VTODO (RFC 5545).
+fn to_vtodo(task: &Task) -> Result<String> {
+    let mut todo = ContentLines::new();
+    todo.raw("BEGIN", "VTODO");
+    todo.text("UID", &format!("{}@pff-cli", task.id()?));
+
+    let stamp = task
+        .modification_time()?
+        .or(task.creation_time()?)
+        .unwrap_or_else(|| Utc::now().naive_utc());
+    todo.time("DTSTAMP", stamp);
+
+    todo.opt_text("SUMMARY", task.subject()?.as_deref());
+    todo.opt_text("DESCRIPTION", task.body()?.as_deref());
+
+    // a recurrence needs a start to recur from
+    let start_date = task.start_date()?;
+    let due_date = task.due_date()?;
+    let rule = match (start_date, task.recurrence()?) {
+        (Some(_), Some(pattern)) => {
+            let until = match pattern.end {
+                RecurrenceEnd::Date(date) => Some(format_date(date)),
+                _ => None,
+            };
+            calendar::rrule(&pattern, until)
+        }
+        _ => None,
+    };
+
+    // DUE has to be later than DTSTART, which it isn't for tasks that start
+    // and are due on the same day
+    let (start_date, due_date) = match (start_date, due_date) {
+        // recurring tasks keep their start and are due at the end of the day
+        (Some(start), Some(due)) if due <= start && rule.is_some() => {
+            (Some(start), start.succ_opt())
+        }
+        // other tasks leave out the start
+        (Some(start), Some(due)) if due <= start => (None, Some(due)),
+        dates => dates,
+    };
+    if let Some(start_date) = start_date {
+        todo.raw("DTSTART;VALUE=DATE", &format_date(start_date));
+    }
+    if let Some(due_date) = due_date {
+        todo.raw("DUE;VALUE=DATE", &format_date(due_date));
+    }
+    if let Some(rule) = rule {
+        todo.raw("RRULE", &rule);
+    }
+
+    let status = match task.is_complete()? {
+        true => Some(TaskStatus::Complete),
+        false => task.status()?,
+    };
+    if let Some(status) = status {
+        let status = match status {
+            TaskStatus::NotStarted | TaskStatus::Waiting | TaskStatus::Deferred => "NEEDS-ACTION",
+            TaskStatus::InProgress => "IN-PROCESS",
+            TaskStatus::Complete => "COMPLETED",
+        };
+        todo.raw("STATUS", status);
+    }
+    if let Some(percent_complete) = task.percent_complete()? {
+        todo.raw("PERCENT-COMPLETE", &percent_complete.to_string());
+    }
+    if let Some(date_completed) = task.date_completed()? {
+        todo.time("COMPLETED", date_completed);
+    }
+
+    // iCalendar has no properties for the effort so keep it in extension
+    // properties rather than lose it
+    let efforts = [
+        ("X-PFF-TOTAL-EFFORT", task.total_effort()?),
+        ("X-PFF-ACTUAL-EFFORT", task.actual_effort()?),
+    ];
+    for (name, effort) in efforts {
+        if let Some(effort) = effort.filter(|effort| *effort > Duration::zero()) {
+            todo.raw(name, &format!("PT{}M", effort.num_minutes()));
+        }
+    }
+
+    todo.raw("END", "VTODO");
+
+    Ok(todo.into_string())
+}
@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+use pff::{
+    item::ItemKind,
+    message::Message,
+    path::{FolderPath, ItemIdPath},
+    threads::{ThreadInfo, Threader},
+    walk::Walker,
+    FileOpenFlags, Pff,
+};
+use serde::Serialize;
+
+#[derive(ValueEnum, Clone, Copy, PartialOrd, Ord, Eq, Debug, PartialEq)]
+pub(crate) enum OutputFormat {
+    Json,
+    Text,
+}
+
+#[derive(Serialize, Debug)]
+struct ThreadNode {
+    id: String,
+    topic: Option<String>,
+    messages: Vec<MessageNode>,
+}
+
+#[derive(Serialize, Debug)]
+struct MessageNode {
+    id: String,
+    folder: Option<String>,
+    subject: Option<String>,
+    sender: Option<String>,
+    time: Option<NaiveDateTime>,
+}
+
+pub(crate) async fn run(
+    pff_file: PathBuf,
+    format: OutputFormat,
+    min_messages: usize,
+) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    // a message that can't be read is no reason to leave out all the others
+    let mut threader = Threader::new();
+    for entry in Walker::new(&pff)
+        .kinds([ItemKind::Email])
+        .continue_on_error(true)
+    {
+        let (folder_path, id_path, message) = match entry {
+            Ok((folder_path, id_path, item)) => match item.into_message() {
+                Some(message) => (folder_path, id_path, message),
+                None => continue,
+            },
+            Err(error) => {
+                eprintln!("Failed to read {error}");
+                continue;
+            }
+        };
+
+        if let Err(err) = add_message(&folder_path, &id_path, &message, &mut threader) {
+            eprintln!("Failed to read message {id_path}: {err}");
+        }
+    }
+
+    let threads = threader
+        .threads()
+        .into_iter()
+        .filter(|thread| thread.messages.len() >= min_messages)
+        .map(|thread| ThreadNode {
+            id: thread.id,
+            topic: thread.topic,
+            messages: thread
+                .messages
+                .into_iter()
+                .map(|(message, info)| MessageNode {
+                    time: info.time,
+                    ..message
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string(&threads)?),
+        OutputFormat::Text => threads.iter().for_each(print_thread),
+    }
+
+    Ok(())
+}
+
+fn add_message(
+    folder_path: &FolderPath,
+    id_path: &ItemIdPath,
+    message: &Message,
+    threader: &mut Threader<MessageNode>,
+) -> Result<()> {
+    let node = MessageNode {
+        id: id_path.to_string(),
+        folder: folder_path
+            .names()
+            .last()
+            .filter(|name| !name.is_empty())
+            .cloned(),
+        subject: message.subject()?,
+        sender: message.sender()?,
+        time: None,
+    };
+    threader.add(node, ThreadInfo::from_message(message)?);
+
+    Ok(())
+}
+
+fn print_thread(thread: &ThreadNode) {
+    println!(
+        "{} ({} messages) [{}]",
+        thread.topic.as_deref().unwrap_or("(no topic)"),
+        thread.messages.len(),
+        thread.id,
+    );
+
+    for message in &thread.messages {
+        println!(
+            "  {} {} | {} | {} [{}]",
+            message
+                .time
+                .map(|time| time.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            message.folder.as_deref().unwrap_or("-"),
+            message.sender.as_deref().unwrap_or("-"),
+            message.subject.as_deref().unwrap_or("--"),
+            message.id,
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use itertools::Itertools;
+use pff::{folder::Folder, item::ItemExt, FileOpenFlags, Pff};
+use serde::Serialize;
+
+#[derive(ValueEnum, Clone, Copy, PartialOrd, Ord, Eq, Debug, PartialEq)]
+pub(crate) enum OutputFormat {
+    Json,
+    Text,
+}
+
+#[derive(Serialize, Debug)]
+struct FolderNode {
+    id: u32,
+    id_path: String,
+    name: Option<String>,
+    container_class: Option<String>,
+    message_count: i32,
+    unread_count: Option<u32>,
+    sub_folder_count: i32,
+    sub_folders: Vec<FolderNode>,
+}
+
+pub(crate) async fn run(pff_file: PathBuf, format: OutputFormat) -> Result<()> {
+    // open pst/ost file
+    let pff = Pff::new()?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
+
+    if let Some(root_folder) = pff.root_folder()? {
+        let root = to_node(&root_folder.into_folder()?, &mut vec![])?;
+
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(&root)?),
+            OutputFormat::Text => print_tree(&root, 0),
+        }
+    }
+
+    Ok(())
+}
+
+fn to_node(folder: &Folder, id_path: &mut Vec<u32>) -> Result<FolderNode> {
+    let mut sub_folders = vec![];
+    for sub_folder in folder.sub_folders()? {
+        let sub_folder = sub_folder?;
+        id_path.push(sub_folder.id()?);
+        sub_folders.push(to_node(&sub_folder, id_path)?);
+        id_path.pop();
+    }
+
+    Ok(FolderNode {
+        id: folder.id()?,
+        // the ID path doesn't include the root folder's ID so that it can be
+        // passed as is to the other commands
+        id_path: id_path.iter().join("_"),
+        name: folder.name()?,
+        container_class: folder.container_class()?,
+        message_count: folder.messages_count()?,
+        unread_count: folder.unread_count()?,
+        sub_folder_count: folder.sub_folders_count()?,
+        sub_folders,
+    })
+}
+
+fn print_tree(node: &FolderNode, depth: usize) {
+    let name = match (depth, node.name.as_deref()) {
+        (_, Some(name)) => name,
+        (0, None) => "(root)",
+        (_, None) => "(unnamed)",
+    };
+    let id_path = match node.id_path.is_empty() {
+        true => "-",
+        false => &node.id_path,
+    };
+
+    println!(
+        "{:indent$}{name} [{id_path}] {} messages: {}, unread: {}, subfolders: {}",
+        "",
+        node.container_class.as_deref().unwrap_or("-"),
+        node.message_count,
+        node.unread_count.unwrap_or_default(),
+        node.sub_folder_count,
+        indent = depth * 2,
+    );
+
+    for sub_folder in &node.sub_folders {
+        print_tree(sub_folder, depth + 1);
+    }
+}
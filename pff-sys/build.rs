@@ -7,6 +7,9 @@ fn main() {
         .probe("libpff")
         .unwrap();
 
+    // libbfio is needed so we can hand libpff our own file IO handles
+    pkg_config::Config::new().probe("libbfio").unwrap();
+
     // Tell cargo to invalidate the built crate whenever the wrapper changes
     println!("cargo:rerun-if-changed=wrapper.h");
 
@@ -1 +1,4 @@
-#include <libpff.h>
\ No newline at end of file
+#define LIBPFF_HAVE_BFIO
+
+#include <libbfio.h>
+#include <libpff.h>
@@ -12,9 +12,6 @@ pub enum Error {
     #[error("PFF error {0}")]
     Pff(#[from] pff::error::Error),
 
-    #[error("Invalid message ID")]
-    ParseInt(#[from] std::num::ParseIntError),
-
     #[error("Receiving channel for message body in web server thread has closed")]
     BodyChannelClosed,
 
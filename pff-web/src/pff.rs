@@ -6,7 +6,7 @@ use std::{
 };
 
 use log::trace;
-use pff::{item::ItemExt, message::Message as PffMessage, FileOpenFlags, Pff, PffOpen};
+use pff::{message::Message as PffMessage, path::ItemIdPath, FileOpenFlags, Pff, PffOpen};
 use tokio::{
     sync::{mpsc, watch},
     time::timeout,
@@ -101,13 +101,7 @@ fn pff_loop(manager: PffManager, mut command_rx: mpsc::Receiver<String>) -> Resu
 
     trace!("Loading PFF file: {:?}", pff_file);
     let pff = Pff::new()?;
-    let pff = pff.open(
-        pff_file
-            .as_path()
-            .to_str()
-            .expect("Path to PFF file is invalid."),
-        FileOpenFlags::READ,
-    )?;
+    let pff = pff.open_path(&pff_file, FileOpenFlags::READ)?;
     trace!("PFF opened.");
 
     let message_tx = manager.state.lock().unwrap().body_tx.clone();
@@ -122,26 +116,14 @@ fn pff_loop(manager: PffManager, mut command_rx: mpsc::Receiver<String>) -> Resu
 }
 
 fn locate_message(pff: &PffOpen, id: &str) -> Result<Body, Error> {
-    // parse the id path to the message
-    let id_path = id
-        .split('_')
-        .map(str::parse::<u32>)
-        .collect::<Vec<Result<_, _>>>()
-        .into_iter()
-        .collect::<Result<Vec<_>, _>>()?;
-
     // navigate the tree to get to the message
-    let mut item_ref = pff.root_folder()?;
-    let mut index = 0;
-    while let (Some(item), true) = (item_ref.as_ref(), index < id_path.len()) {
-        item_ref = item.sub_item_by_id(id_path[index])?;
-        index += 1;
-    }
-
-    if let Some(item) = item_ref {
-        let message: PffMessage = item.into();
-        Ok(message.body()?.map(Body::from).ok_or(Error::BodyNotFound)?)
-    } else {
-        Err(Error::BodyNotFound)
-    }
+    let id_path = id.parse::<ItemIdPath>()?;
+    let item = match id_path.resolve(pff) {
+        Ok(item) => item,
+        Err(pff::error::Error::ItemNotFound(_)) => return Err(Error::BodyNotFound),
+        Err(error) => return Err(error.into()),
+    };
+
+    let message: PffMessage = item.into();
+    message.body()?.map(Body::from).ok_or(Error::BodyNotFound)
 }
@@ -9,6 +9,8 @@ use crate::error::Error;
 pub(crate) struct Agent {
     pub(crate) name: Option<String>,
     pub(crate) email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) kind: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
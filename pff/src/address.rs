@@ -0,0 +1,226 @@
+use encoding_rs::WINDOWS_1252;
+
+/// Provider UID that identifies a one-off entry ID, see [MS-OXCDATA]
+/// section 2.2.5.1.
+///
+/// [MS-OXCDATA]: https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxcdata
+pub(crate) const ONE_OFF_PROVIDER_UID: [u8; 16] = [
+    0x81, 0x2b, 0x1f, 0xa4, 0xbe, 0xa3, 0x10, 0x19, 0x9d, 0x6e, 0x00, 0xdd, 0x01, 0x0f, 0x54, 0x02,
+];
+
+/// Flag in a one-off entry ID that says the strings are UTF-16 encoded.
+const ONE_OFF_UNICODE: u16 = 0x8000;
+
+/// An entry ID that carries the address it refers to instead of pointing
+/// at an address book entry.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OneOffEntryId {
+    pub display_name: String,
+    pub address_type: String,
+    pub email_address: String,
+}
+
+impl OneOffEntryId {
+    /// Parses `data` as a one-off entry ID. Returns `None` if `data` is some
+    /// other kind of entry ID or is malformed.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        // 4 bytes of flags, the provider UID, 2 bytes of version and 2 bytes
+        // of flags followed by the three null terminated strings
+        if data.len() < 24 || data[4..20] != ONE_OFF_PROVIDER_UID {
+            return None;
+        }
+
+        let flags = u16::from_le_bytes([data[22], data[23]]);
+        let mut strings = NullTerminatedStrings {
+            data: &data[24..],
+            unicode: flags & ONE_OFF_UNICODE != 0,
+        };
+
+        Some(OneOffEntryId {
+            display_name: strings.next()?,
+            address_type: strings.next()?,
+            email_address: strings.next()?,
+        })
+    }
+}
+
+struct NullTerminatedStrings<'a> {
+    data: &'a [u8],
+    unicode: bool,
+}
+
+impl Iterator for NullTerminatedStrings<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.unicode {
+            let units = self
+                .data
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .take_while(|&unit| unit != 0)
+                .collect::<Vec<_>>();
+            // the string must be terminated
+            let end = (units.len() + 1) * 2;
+            if end > self.data.len() {
+                return None;
+            }
+            self.data = &self.data[end..];
+            String::from_utf16(&units).ok()
+        } else {
+            let len = self.data.iter().position(|&b| b == 0)?;
+            let (value, _) = WINDOWS_1252.decode_without_bom_handling(&self.data[..len]);
+            self.data = &self.data[len + 1..];
+            Some(value.into_owned())
+        }
+    }
+}
+
+/// The properties of an address that can be used to work out its SMTP
+/// address.
+#[derive(Debug, Default)]
+pub(crate) struct AddressEntries {
+    pub(crate) address_type: Option<String>,
+    pub(crate) email_address: Option<String>,
+    pub(crate) smtp_address: Option<String>,
+    pub(crate) entry_id: Option<Vec<u8>>,
+}
+
+impl AddressEntries {
+    /// Returns the SMTP address for the address. Exchange addresses hold an
+    /// X.500 legacy DN (`/O=EXCHANGELABS/OU=...`) as email address, so for
+    /// these we try the SMTP address property and then the entry ID. If
+    /// neither yields an SMTP address the legacy DN is returned.
+    ///
+    /// The proxy addresses (`PidTagAddressBookProxyAddresses`) would list the
+    /// SMTP address too but they are kept in the address book rather than in
+    /// PST/OST files, so they aren't used.
+    pub(crate) fn resolve(self) -> Option<String> {
+        let email_address = non_empty(self.email_address);
+
+        if !is_exchange_address(self.address_type.as_deref(), email_address.as_deref()) {
+            return email_address;
+        }
+
+        non_empty(self.smtp_address)
+            .or_else(|| {
+                self.entry_id
+                    .as_deref()
+                    .and_then(OneOffEntryId::parse)
+                    .filter(|one_off| one_off.address_type.eq_ignore_ascii_case("SMTP"))
+                    .and_then(|one_off| non_empty(Some(one_off.email_address)))
+            })
+            .or(email_address)
+    }
+}
+
+fn is_exchange_address(address_type: Option<&str>, email_address: Option<&str>) -> bool {
+    match address_type {
+        Some(address_type) => address_type.eq_ignore_ascii_case("EX"),
+        // legacy DNs always start with the organization
+        None => email_address.is_some_and(|email| email.to_ascii_lowercase().starts_with("/o=")),
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::{AddressEntries, OneOffEntryId, ONE_OFF_PROVIDER_UID};
+
+    const LEGACY_DN: &str =
+        "/O=EXCHANGELABS/OU=EXCHANGE ADMINISTRATIVE GROUP/CN=RECIPIENTS/CN=ALICE";
+
+    fn one_off(unicode: bool, strings: &[&str]) -> Vec<u8> {
+        let mut data = vec![0; 4];
+        data.extend_from_slice(&ONE_OFF_PROVIDER_UID);
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&(if unicode { 0x8000u16 } else { 0 }).to_le_bytes());
+        for s in strings {
+            match unicode {
+                true => s
+                    .encode_utf16()
+                    .chain([0])
+                    .for_each(|u| data.extend_from_slice(&u.to_le_bytes())),
+                false => {
+                    data.extend_from_slice(s.as_bytes());
+                    data.push(0);
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn parse_one_off() {
+        for unicode in [true, false] {
+            let data = one_off(unicode, &["Alice", "SMTP", "alice@email.com"]);
+            assert_eq!(
+                OneOffEntryId::parse(&data).unwrap(),
+                OneOffEntryId {
+                    display_name: "Alice".to_string(),
+                    address_type: "SMTP".to_string(),
+                    email_address: "alice@email.com".to_string(),
+                }
+            );
+        }
+
+        let data = one_off(true, &["Alice", "SMTP"]);
+        assert!(OneOffEntryId::parse(&data).is_none());
+        assert!(OneOffEntryId::parse(&[0; 24]).is_none());
+    }
+
+    #[test]
+    fn resolve_smtp() {
+        let entries = AddressEntries {
+            address_type: Some("SMTP".to_string()),
+            email_address: Some("alice@email.com".to_string()),
+            smtp_address: Some("other@email.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(entries.resolve().unwrap(), "alice@email.com");
+    }
+
+    #[test]
+    fn resolve_exchange() {
+        let entries = AddressEntries {
+            address_type: Some("EX".to_string()),
+            email_address: Some(LEGACY_DN.to_string()),
+            smtp_address: Some("alice@email.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(entries.resolve().unwrap(), "alice@email.com");
+
+        let entries = AddressEntries {
+            address_type: Some("EX".to_string()),
+            email_address: Some(LEGACY_DN.to_string()),
+            entry_id: Some(one_off(true, &["Alice", "SMTP", "alice@email.com"])),
+            ..Default::default()
+        };
+        assert_eq!(entries.resolve().unwrap(), "alice@email.com");
+
+        let entries = AddressEntries {
+            address_type: Some("EX".to_string()),
+            email_address: Some(LEGACY_DN.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(entries.resolve().unwrap(), LEGACY_DN);
+    }
+
+    #[test]
+    fn resolve_blank_smtp_address() {
+        // a blank SMTP address falls through to the entry ID, for legacy DNs
+        // without an address type too
+        let entries = AddressEntries {
+            email_address: Some(LEGACY_DN.to_string()),
+            smtp_address: Some(" ".to_string()),
+            entry_id: Some(one_off(false, &["Alice", "SMTP", "alice@email.com"])),
+            ..Default::default()
+        };
+        assert_eq!(entries.resolve().unwrap(), "alice@email.com");
+    }
+}
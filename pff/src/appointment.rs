@@ -0,0 +1,169 @@
+use std::{fmt::Display, ptr};
+
+use chrono::NaiveDateTime;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use pff_sys::{libpff_item_free, libpff_item_t};
+
+use crate::{
+    error::Error,
+    item::{EntryType, Item, ItemExt},
+    namedproperty::{property_set, NamedProperty},
+    recurrence::{AppointmentRecurrence, TimeZone},
+};
+
+/// LID of `PidLidCleanGlobalObjectId` in the meeting property set.
+const CLEAN_GLOBAL_OBJECT_ID: u32 = 0x0023;
+
+/// LID of `PidLidGlobalObjectId` in the meeting property set.
+const GLOBAL_OBJECT_ID: u32 = 0x0003;
+
+/// How the time of an appointment shows up in the free/busy information as
+/// given by `PidLidBusyStatus`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum BusyStatus {
+    Free = 0,
+    Tentative = 1,
+    Busy = 2,
+    OutOfOffice = 3,
+    WorkingElsewhere = 4,
+}
+
+impl Display for BusyStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BusyStatus::Free => write!(f, "free"),
+            BusyStatus::Tentative => write!(f, "tentative"),
+            BusyStatus::Busy => write!(f, "busy"),
+            BusyStatus::OutOfOffice => write!(f, "out of office"),
+            BusyStatus::WorkingElsewhere => write!(f, "working elsewhere"),
+        }
+    }
+}
+
+/// A calendar item, i.e., an `IPM.Appointment` message.
+#[derive(Debug)]
+pub struct Appointment {
+    item: *mut libpff_item_t,
+}
+
+impl Default for Appointment {
+    fn default() -> Self {
+        Appointment {
+            item: ptr::null_mut(),
+        }
+    }
+}
+
+impl Drop for Appointment {
+    fn drop(&mut self) {
+        unsafe { libpff_item_free(&mut self.item, ptr::null_mut()) };
+    }
+}
+
+impl Item for Appointment {
+    fn new(item: *mut libpff_item_t) -> Self {
+        Appointment { item }
+    }
+
+    fn item(&self) -> *mut libpff_item_t {
+        self.item
+    }
+
+    fn detach(mut self) -> *mut libpff_item_t {
+        let item = self.item;
+        self.item = ptr::null_mut();
+        item
+    }
+}
+
+macro_rules! prop_string {
+    ($method:ident, $entry_type:ident) => {
+        pub fn $method(&self) -> Result<Option<String>, Error> {
+            self.get_string_value(EntryType::$entry_type)
+        }
+    };
+}
+
+impl Appointment {
+    prop_string!(subject, MessageSubject);
+    prop_string!(location, AppointmentLocation);
+    prop_string!(body, MessageBodyPlainText);
+    // A description of the recurrence for display, for e.g. "every Monday
+    // from 09:00 to 09:30".
+    prop_string!(recurrence_pattern, AppointmentRecurrencePattern);
+    prop_string!(timezone_description, AppointmentTimezoneDescription);
+
+    /// The start time in UTC.
+    pub fn start_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::AppointmentStartTime)
+    }
+
+    /// The end time in UTC.
+    pub fn end_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::AppointmentEndTime)
+    }
+
+    /// The duration in minutes.
+    pub fn duration(&self) -> Result<Option<u32>, Error> {
+        self.get_u32(EntryType::AppointmentDuration)
+    }
+
+    pub fn busy_status(&self) -> Result<Option<BusyStatus>, Error> {
+        self.get_u32(EntryType::AppointmentBusyStatus)?
+            .map(|status| BusyStatus::try_from(status).map_err(|_| Error::BadBusyStatus(status)))
+            .transpose()
+    }
+
+    pub fn is_all_day(&self) -> Result<bool, Error> {
+        Ok(self
+            .get_bool(EntryType::AppointmentSubType)?
+            .unwrap_or_default())
+    }
+
+    pub fn is_recurring(&self) -> Result<bool, Error> {
+        Ok(self
+            .get_bool(EntryType::AppointmentIsRecurring)?
+            .unwrap_or_default())
+    }
+
+    /// The decoded recurrence of a recurring appointment. Returns `None` if
+    /// the appointment doesn't recur or the recurrence can't be decoded.
+    pub fn recurrence(&self) -> Result<Option<AppointmentRecurrence>, Error> {
+        Ok(self
+            .first_entry_by_type(EntryType::AppointmentRecurrenceData)?
+            .map(|entry| entry.as_buffer())
+            .transpose()?
+            .and_then(|data| AppointmentRecurrence::parse(&data)))
+    }
+
+    /// The time zone the recurrence of a recurring appointment is in.
+    pub fn time_zone(&self) -> Result<Option<TimeZone>, Error> {
+        Ok(self
+            .first_entry_by_type(EntryType::AppointmentTimezoneStruct)?
+            .map(|entry| entry.as_buffer())
+            .transpose()?
+            .and_then(|data| TimeZone::parse(&data)))
+    }
+
+    /// The ID that identifies the appointment across calendars, for e.g. to
+    /// use as the iCalendar UID.
+    pub fn global_object_id(&self) -> Result<Option<Vec<u8>>, Error> {
+        for id in [CLEAN_GLOBAL_OBJECT_ID, GLOBAL_OBJECT_ID] {
+            let property = NamedProperty::new(property_set::PSETID_MEETING, id);
+            if let Some(entry) = self.entry_by_named_property(&property)? {
+                return entry.as_buffer().map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn creation_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::MessageCreationTime)
+    }
+
+    pub fn modification_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::MessageModificationTime)
+    }
+}
@@ -1,12 +1,22 @@
-use std::ptr;
+use std::{
+    io::{self, Read, Seek, SeekFrom},
+    ptr,
+};
 
+use bitflags::bitflags;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use pff_sys::{
-    libpff_attachment_data_read_buffer, libpff_attachment_get_data_size,
-    libpff_attachment_get_type, libpff_error_t, libpff_item_free, libpff_item_t,
+    libpff_attachment_data_read_buffer, libpff_attachment_data_seek_offset,
+    libpff_attachment_get_data_size, libpff_attachment_get_item, libpff_attachment_get_type,
+    libpff_error_t, libpff_item_free, libpff_item_t,
 };
 
-use crate::{error::Error, item::Item};
+use crate::{
+    error::Error,
+    item::{EntryType, Item, ItemExt},
+    message::Message,
+    recordset::Seek as Whence,
+};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
 #[repr(i32)]
@@ -17,6 +27,29 @@ pub enum AttachmentType {
     Reference = 'r' as i32,
 }
 
+/// How the attachment data is stored as given by `PidTagAttachMethod`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum AttachmentMethod {
+    None = 0,
+    ByValue = 1,
+    ByReference = 2,
+    ByReferenceResolve = 3,
+    ByReferenceOnly = 4,
+    EmbeddedMessage = 5,
+    Ole = 6,
+    ByWebReference = 7,
+}
+
+bitflags! {
+    /// Rendering hints stored in `PidTagAttachFlags`.
+    pub struct AttachmentFlags: u32 {
+        const INVISIBLE_IN_HTML = 0x0001;
+        const INVISIBLE_IN_RTF = 0x0002;
+        const RENDERED_IN_BODY = 0x0004;
+    }
+}
+
 #[derive(Debug)]
 pub struct Attachment {
     item: *mut libpff_item_t,
@@ -52,6 +85,14 @@ impl Item for Attachment {
     }
 }
 
+macro_rules! prop_string {
+    ($method:ident, $entry_type:ident) => {
+        pub fn $method(&self) -> Result<Option<String>, Error> {
+            self.get_string_value(EntryType::$entry_type)
+        }
+    };
+}
+
 impl Attachment {
     pub fn type_(&self) -> Result<AttachmentType, Error> {
         let mut attachment_type: i32 = 0;
@@ -66,6 +107,67 @@ impl Attachment {
         }
     }
 
+    prop_string!(long_filename, AttachmentFilenameLong);
+    prop_string!(short_filename, AttachmentFilenameShort);
+    prop_string!(extension, AttachmentExtension);
+    prop_string!(mime_tag, AttachmentMimeTag);
+    prop_string!(content_id, AttachmentContentId);
+    prop_string!(content_location, AttachmentContentLocation);
+
+    /// The best available file name for the attachment: the long file name,
+    /// then the short (8.3) file name and finally the display name.
+    pub fn file_name(&self) -> Result<Option<String>, Error> {
+        Ok(match self.long_filename()? {
+            Some(name) => Some(name),
+            None => match self.short_filename()? {
+                Some(name) => Some(name),
+                None => self.display_name()?,
+            },
+        })
+    }
+
+    pub fn method(&self) -> Result<Option<AttachmentMethod>, Error> {
+        self.get_u32(EntryType::AttachmentMethod)?
+            .map(|method| {
+                AttachmentMethod::try_from(method).map_err(|_| Error::BadAttachmentMethod(method))
+            })
+            .transpose()
+    }
+
+    pub fn flags(&self) -> Result<AttachmentFlags, Error> {
+        Ok(AttachmentFlags::from_bits_retain(
+            self.get_u32(EntryType::AttachmentFlags)?
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// The character offset in the RTF body where the attachment is rendered.
+    /// `None` when the attachment isn't rendered in the body.
+    pub fn rendering_position(&self) -> Result<Option<u32>, Error> {
+        Ok(self
+            .get_u32(EntryType::AttachmentRenderingPosition)?
+            .filter(|&pos| pos != u32::MAX))
+    }
+
+    /// Hidden attachments are not shown in the list of attachments, these
+    /// are typically images referenced from the HTML body.
+    pub fn is_hidden(&self) -> Result<bool, Error> {
+        Ok(self
+            .get_bool(EntryType::AttachmentHidden)?
+            .unwrap_or_default())
+    }
+
+    /// Whether the attachment is shown as part of the message body, for e.g.
+    /// an image referenced from the HTML body through its content ID.
+    pub fn is_inline(&self) -> Result<bool, Error> {
+        if self.flags()?.contains(AttachmentFlags::RENDERED_IN_BODY) {
+            return Ok(true);
+        }
+
+        let referenced = self.content_id()?.is_some() || self.content_location()?.is_some();
+        Ok(referenced && self.is_hidden()?)
+    }
+
     pub fn data_size(&self) -> Result<u64, Error> {
         let mut data_size: u64 = 0;
         let mut error: *mut libpff_error_t = ptr::null_mut();
@@ -81,6 +183,8 @@ impl Attachment {
     pub fn as_buffer(&self) -> Result<Vec<u8>, Error> {
         let mut error: *mut libpff_error_t = ptr::null_mut();
         let data_size = self.data_size()?;
+        // a reader may have moved the read offset
+        self.data_seek_offset(0, Whence::Set)?;
         let mut buf = Vec::<u8>::with_capacity(data_size as usize);
         let buf_ptr = buf.as_mut_ptr();
 
@@ -102,4 +206,108 @@ impl Attachment {
             _ => Ok(buf),
         }
     }
+
+    /// Returns a reader over the attachment data positioned at the start of
+    /// the data. Unlike `as_buffer` this doesn't load the whole attachment
+    /// into memory.
+    pub fn reader(&self) -> Result<AttachmentReader<'_>, Error> {
+        Ok(AttachmentReader {
+            attachment: self,
+            position: 0,
+        })
+    }
+
+    fn data_seek_offset(&self, offset: i64, whence: Whence) -> Result<u64, Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+
+        let res = unsafe {
+            libpff_attachment_data_seek_offset(self.item(), offset, whence.into(), &mut error)
+        };
+        match res {
+            -1 => Err(Error::pff_error(error)),
+            _ => Ok(res as u64),
+        }
+    }
+
+    /// Opens the message attached to an `AttachmentType::Item` attachment,
+    /// for e.g. a forwarded mail. Returns `None` for any other kind of
+    /// attachment.
+    pub fn embedded_message(&self) -> Result<Option<Message>, Error> {
+        if self.type_()? != AttachmentType::Item {
+            return Ok(None);
+        }
+
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut item: *mut libpff_item_t = ptr::null_mut();
+
+        let res = unsafe { libpff_attachment_get_item(self.item(), &mut item, &mut error) };
+        match res {
+            0 => Ok(None),
+            1 => Ok(Some(Message::new(item))),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+}
+
+/// Streams the data of an attachment. libpff tracks a single read offset
+/// on the attachment, so the reader keeps its own position and seeks to it
+/// before every read. That way several readers, or a reader and
+/// `Attachment::as_buffer`, can be used on the same attachment.
+#[derive(Debug)]
+pub struct AttachmentReader<'a> {
+    attachment: &'a Attachment,
+    position: u64,
+}
+
+impl Read for AttachmentReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let position = i64::try_from(self.position).map_err(|_| io::ErrorKind::InvalidInput)?;
+        self.attachment
+            .data_seek_offset(position, Whence::Set)
+            .map_err(io::Error::other)?;
+
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+
+        let res = unsafe {
+            libpff_attachment_data_read_buffer(
+                self.attachment.item(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut error,
+            )
+        };
+        match res {
+            -1 => Err(io::Error::other(Error::pff_error(error))),
+            _ => {
+                self.position += res as u64;
+                Ok(res as usize)
+            }
+        }
+    }
+}
+
+impl Seek for AttachmentReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => (
+                i64::try_from(offset).map_err(|_| io::ErrorKind::InvalidInput)?,
+                Whence::Set,
+            ),
+            // libpff's offset may have been moved by someone else
+            SeekFrom::Current(offset) => (
+                i64::try_from(self.position)
+                    .ok()
+                    .and_then(|position| position.checked_add(offset))
+                    .ok_or(io::ErrorKind::InvalidInput)?,
+                Whence::Set,
+            ),
+            SeekFrom::End(offset) => (offset, Whence::End),
+        };
+
+        self.position = self
+            .attachment
+            .data_seek_offset(offset, whence)
+            .map_err(io::Error::other)?;
+        Ok(self.position)
+    }
 }
@@ -0,0 +1,143 @@
+use std::ptr;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use pff_sys::{libpff_item_free, libpff_item_t};
+
+use crate::{
+    address::AddressEntries,
+    error::Error,
+    item::{EntryType, Item, ItemExt},
+};
+
+/// Entry types of the address type, address and original entry ID of the
+/// three email addresses a contact can have.
+const EMAIL_ADDRESS_ENTRIES: [(EntryType, EntryType, EntryType); 3] = [
+    (
+        EntryType::ContactEmailAddressType1,
+        EntryType::ContactEmailAddress1,
+        EntryType::ContactEmailEntryIdentifier1,
+    ),
+    (
+        EntryType::ContactEmailAddressType2,
+        EntryType::ContactEmailAddress2,
+        EntryType::ContactEmailEntryIdentifier2,
+    ),
+    (
+        EntryType::ContactEmailAddressType3,
+        EntryType::ContactEmailAddress3,
+        EntryType::ContactEmailEntryIdentifier3,
+    ),
+];
+
+/// A contact item, i.e., an `IPM.Contact` message.
+#[derive(Debug)]
+pub struct Contact {
+    item: *mut libpff_item_t,
+}
+
+impl Default for Contact {
+    fn default() -> Self {
+        Contact {
+            item: ptr::null_mut(),
+        }
+    }
+}
+
+impl Drop for Contact {
+    fn drop(&mut self) {
+        unsafe { libpff_item_free(&mut self.item, ptr::null_mut()) };
+    }
+}
+
+impl Item for Contact {
+    fn new(item: *mut libpff_item_t) -> Self {
+        Contact { item }
+    }
+
+    fn item(&self) -> *mut libpff_item_t {
+        self.item
+    }
+
+    fn detach(mut self) -> *mut libpff_item_t {
+        let item = self.item;
+        self.item = ptr::null_mut();
+        item
+    }
+}
+
+macro_rules! prop_string {
+    ($method:ident, $entry_type:ident) => {
+        pub fn $method(&self) -> Result<Option<String>, Error> {
+            self.get_string_value(EntryType::$entry_type)
+        }
+    };
+}
+
+impl Contact {
+    prop_string!(given_name, ContactGivenName);
+    prop_string!(surname, ContactSurname);
+    prop_string!(initials, ContactInitials);
+    prop_string!(nickname, ContactNickname);
+    // The name prefix, for e.g. "Dr.".
+    prop_string!(title, ContactTitle);
+    // The name suffix, for e.g. "Jr.".
+    prop_string!(generational_abbreviation, ContactGenerationalAbbreviation);
+    // The name the contact is filed under, for e.g. "Doe, Jane".
+    prop_string!(file_under, AddressFileUnder);
+
+    prop_string!(company_name, ContactCompanyName);
+    prop_string!(department_name, ContactDepartmentName);
+    prop_string!(job_title, ContactJobTitle);
+    prop_string!(office_location, ContactOfficeLocation);
+    prop_string!(business_home_page, ContactBusinessHomePage);
+
+    prop_string!(primary_phone_number, ContactPrimaryPhoneNumber);
+    prop_string!(business_phone_number, ContactBusinessPhoneNumber1);
+    prop_string!(business_phone_number2, ContactBusinessPhoneNumber2);
+    prop_string!(home_phone_number, ContactHomePhoneNumber);
+    prop_string!(mobile_phone_number, ContactMobilePhoneNumber);
+    prop_string!(business_fax_number, ContactBusinessFaxNumber);
+    prop_string!(callback_phone_number, ContactCallbackPhoneNumber);
+
+    // The full mailing address as a single multi-line string.
+    prop_string!(postal_address, ContactPostalAddress);
+    prop_string!(street_address, ContactStreetAddress);
+    prop_string!(locality, ContactLocality);
+    prop_string!(state_or_province, ContactStateOrProvince);
+    prop_string!(postal_code, ContactPostalCode);
+    prop_string!(country, ContactCountry);
+
+    prop_string!(notes, MessageBodyPlainText);
+
+    /// The contact's email addresses in the order they appear in Outlook.
+    /// Exchange addresses are resolved to SMTP addresses where possible.
+    pub fn email_addresses(&self) -> Result<Vec<String>, Error> {
+        let mut email_addresses = vec![];
+
+        for (address_type, email_address, entry_id) in EMAIL_ADDRESS_ENTRIES {
+            let entries = AddressEntries {
+                address_type: self.get_string_value(address_type)?,
+                email_address: self.get_string_value(email_address)?,
+                smtp_address: None,
+                entry_id: self
+                    .first_entry_by_type(entry_id)?
+                    .map(|entry| entry.as_buffer())
+                    .transpose()?,
+            };
+
+            if let Some(address) = entries.resolve() {
+                email_addresses.push(address);
+            }
+        }
+
+        Ok(email_addresses)
+    }
+
+    pub fn birthday(&self) -> Result<Option<NaiveDate>, Error> {
+        self.get_date(EntryType::ContactBirthday)
+    }
+
+    pub fn modification_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::MessageModificationTime)
+    }
+}
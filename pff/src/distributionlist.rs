@@ -0,0 +1,288 @@
+use std::ptr;
+
+use chrono::NaiveDateTime;
+use pff_sys::{libpff_item_free, libpff_item_t};
+
+use crate::{
+    address::{AddressEntries, OneOffEntryId},
+    contact::Contact,
+    error::Error,
+    item::{EntryType, Item, ItemExt, ItemType, PffItem},
+    propertyvalue::PropertyValue,
+    PffOpen,
+};
+
+/// Provider UID that identifies a wrapped entry ID, see [MS-OXCDATA]
+/// section 2.2.5.3.
+///
+/// [MS-OXCDATA]: https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxcdata
+const WRAPPED_PROVIDER_UID: [u8; 16] = [
+    0xc0, 0x91, 0xad, 0xd3, 0x51, 0x9d, 0xcf, 0x11, 0xa4, 0xa9, 0x00, 0xaa, 0x00, 0x47, 0xfa, 0xa4,
+];
+
+/// Bit in the type of a wrapped entry ID that says the embedded entry ID
+/// points at a message rather than being a one-off entry ID.
+const WRAPPED_MESSAGE: u8 = 0x80;
+
+/// Size of the entry ID of a message in a PST file: 4 bytes of flags, the
+/// store's provider UID and the node ID of the message.
+const PST_ENTRY_ID_SIZE: usize = 24;
+
+/// What a distribution list member refers to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MemberKind {
+    /// An address that was typed in rather than picked from an address
+    /// book.
+    OneOff,
+    Contact,
+    /// Another distribution list in the same mailbox.
+    DistributionList,
+    /// A user or list in the global address list.
+    AddressBook,
+}
+
+/// A member of a distribution list.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DistributionListMember {
+    pub kind: MemberKind,
+    /// The display name and address of the member as they were when the
+    /// member was added.
+    pub one_off: Option<OneOffEntryId>,
+    /// The identifier of the contact or distribution list the member refers
+    /// to. Only known for PST files, the entry IDs in OST files point into
+    /// the Exchange mailbox instead.
+    pub item_id: Option<u32>,
+}
+
+impl DistributionListMember {
+    /// Parses the wrapped entry ID of a member along with the one-off entry
+    /// ID stored for it. Returns `None` if neither can be decoded.
+    pub fn parse(entry_id: Option<&[u8]>, one_off: Option<&[u8]>) -> Option<Self> {
+        let mut one_off = one_off.and_then(OneOffEntryId::parse);
+
+        let Some((member_type, embedded)) = entry_id.and_then(parse_wrapped_entry_id) else {
+            return one_off.map(|one_off| DistributionListMember {
+                kind: MemberKind::OneOff,
+                one_off: Some(one_off),
+                item_id: None,
+            });
+        };
+
+        // the low 4 bits are the kind, the next 3 the index of the contact's
+        // email address the member uses
+        let kind = match member_type & 0x0f {
+            0x00 => MemberKind::OneOff,
+            0x03 => MemberKind::Contact,
+            0x04 => MemberKind::DistributionList,
+            0x05 | 0x06 => MemberKind::AddressBook,
+            _ => return None,
+        };
+
+        let mut item_id = None;
+        match member_type & WRAPPED_MESSAGE != 0 {
+            true if embedded.len() == PST_ENTRY_ID_SIZE => {
+                let node_id = &embedded[PST_ENTRY_ID_SIZE - 4..];
+                item_id = Some(u32::from_le_bytes(node_id.try_into().ok()?));
+            }
+            true => {}
+            false => one_off = one_off.or_else(|| OneOffEntryId::parse(embedded)),
+        }
+
+        Some(DistributionListMember {
+            kind,
+            one_off,
+            item_id,
+        })
+    }
+
+    pub fn display_name(&self) -> Option<&str> {
+        self.one_off
+            .as_ref()
+            .map(|one_off| one_off.display_name.as_str())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// The member's email address, resolved to an SMTP address where
+    /// possible.
+    pub fn email_address(&self) -> Option<String> {
+        let one_off = self.one_off.as_ref()?;
+
+        AddressEntries {
+            address_type: Some(one_off.address_type.clone()),
+            email_address: Some(one_off.email_address.clone()),
+            ..Default::default()
+        }
+        .resolve()
+    }
+
+    /// Looks up the item the member refers to in `pff`.
+    pub fn resolve(&self, pff: &PffOpen) -> Result<Option<PffItem>, Error> {
+        match self.item_id {
+            Some(item_id) => pff.item_by_id(item_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Looks up the contact the member refers to in `pff`. Returns `None` if
+    /// the member isn't a contact or the contact isn't in the file.
+    pub fn contact(&self, pff: &PffOpen) -> Result<Option<Contact>, Error> {
+        match self.resolve(pff)? {
+            Some(item) if item.type_()? == ItemType::Contact => item.into_contact().map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Returns the type and the embedded entry ID of a wrapped entry ID.
+fn parse_wrapped_entry_id(data: &[u8]) -> Option<(u8, &[u8])> {
+    // 4 bytes of flags, the provider UID and the type
+    if data.len() < 21 || data[4..20] != WRAPPED_PROVIDER_UID {
+        return None;
+    }
+
+    Some((data[20], &data[21..]))
+}
+
+/// A personal distribution list, i.e., an `IPM.DistList` message.
+#[derive(Debug)]
+pub struct DistributionList {
+    item: *mut libpff_item_t,
+}
+
+impl Default for DistributionList {
+    fn default() -> Self {
+        DistributionList {
+            item: ptr::null_mut(),
+        }
+    }
+}
+
+impl Drop for DistributionList {
+    fn drop(&mut self) {
+        unsafe { libpff_item_free(&mut self.item, ptr::null_mut()) };
+    }
+}
+
+impl Item for DistributionList {
+    fn new(item: *mut libpff_item_t) -> Self {
+        DistributionList { item }
+    }
+
+    fn item(&self) -> *mut libpff_item_t {
+        self.item
+    }
+
+    fn detach(mut self) -> *mut libpff_item_t {
+        let item = self.item;
+        self.item = ptr::null_mut();
+        item
+    }
+}
+
+impl DistributionList {
+    pub fn name(&self) -> Result<Option<String>, Error> {
+        match self.get_string_value(EntryType::DistributionListName)? {
+            Some(name) => Ok(Some(name)),
+            None => self.display_name(),
+        }
+    }
+
+    /// The members of the list. Members that can't be decoded are skipped.
+    pub fn members(&self) -> Result<Vec<DistributionListMember>, Error> {
+        // the two properties are parallel lists with an entry for each
+        // member
+        let entry_ids = self.get_binaries(EntryType::DistributionListMemberEntryIdentifiers)?;
+        let one_offs =
+            self.get_binaries(EntryType::DistributionListMemberOneOffEntryIdentifiers)?;
+
+        Ok((0..entry_ids.len().max(one_offs.len()))
+            .filter_map(|index| {
+                DistributionListMember::parse(
+                    entry_ids.get(index).map(Vec::as_slice),
+                    one_offs.get(index).map(Vec::as_slice),
+                )
+            })
+            .collect())
+    }
+
+    pub fn notes(&self) -> Result<Option<String>, Error> {
+        self.get_string_value(EntryType::MessageBodyPlainText)
+    }
+
+    pub fn modification_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::MessageModificationTime)
+    }
+
+    fn get_binaries(&self, entry_type: EntryType) -> Result<Vec<Vec<u8>>, Error> {
+        match self.first_entry_by_type(entry_type)? {
+            Some(entry) => match entry.value()? {
+                PropertyValue::MultiValueBinaryData(values) => Ok(values),
+                PropertyValue::BinaryData(value) => Ok(vec![value]),
+                _ => Ok(vec![]),
+            },
+            None => Ok(vec![]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        address::{OneOffEntryId, ONE_OFF_PROVIDER_UID},
+        distributionlist::{DistributionListMember, MemberKind, WRAPPED_PROVIDER_UID},
+    };
+
+    fn one_off(strings: &[&str]) -> Vec<u8> {
+        let mut data = vec![0; 4];
+        data.extend_from_slice(&ONE_OFF_PROVIDER_UID);
+        data.extend_from_slice(&[0; 4]);
+        for s in strings {
+            data.extend_from_slice(s.as_bytes());
+            data.push(0);
+        }
+        data
+    }
+
+    fn wrapped(member_type: u8, embedded: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 4];
+        data.extend_from_slice(&WRAPPED_PROVIDER_UID);
+        data.push(member_type);
+        data.extend_from_slice(embedded);
+        data
+    }
+
+    #[test]
+    fn parse_members() {
+        let alice = one_off(&["Alice", "SMTP", "alice@email.com"]);
+
+        // a contact in a PST file with node ID 0x200024
+        let mut contact_entry_id = vec![0; 20];
+        contact_entry_id.extend_from_slice(&0x200024u32.to_le_bytes());
+        let member =
+            DistributionListMember::parse(Some(&wrapped(0x83, &contact_entry_id)), Some(&alice))
+                .unwrap();
+        assert_eq!(member.kind, MemberKind::Contact);
+        assert_eq!(member.item_id, Some(0x200024));
+        assert_eq!(member.display_name(), Some("Alice"));
+        assert_eq!(member.email_address().as_deref(), Some("alice@email.com"));
+
+        // a one-off member only has the embedded one-off entry ID
+        let member = DistributionListMember::parse(Some(&wrapped(0x00, &alice)), None).unwrap();
+        assert_eq!(
+            member,
+            DistributionListMember {
+                kind: MemberKind::OneOff,
+                one_off: OneOffEntryId::parse(&alice),
+                item_id: None,
+            }
+        );
+
+        // members of OST files point into the Exchange mailbox
+        let member =
+            DistributionListMember::parse(Some(&wrapped(0x84, &[0; 70])), Some(&alice)).unwrap();
+        assert_eq!(member.kind, MemberKind::DistributionList);
+        assert_eq!(member.item_id, None);
+
+        assert!(DistributionListMember::parse(Some(&[0; 21]), None).is_none());
+    }
+}
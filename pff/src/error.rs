@@ -3,11 +3,19 @@ use std::{ffi::CStr, fmt::Display, os::raw::c_char};
 use pff_sys::{libpff_error_free, libpff_error_sprint, libpff_error_t};
 use thiserror::Error as ThisError;
 
+use crate::item::ValueType;
+
 #[derive(Debug, ThisError)]
 pub enum Error {
     #[error("{0}")]
     PffError(#[source] PffError),
 
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Couldn't create a file IO handle for the reader")]
+    FileIoHandle,
+
     #[error("{0}")]
     NulError(#[from] std::ffi::NulError),
 
@@ -26,20 +34,68 @@ pub enum Error {
     #[error("Unrecognized attachment type {0}")]
     BadAttachmentType(i32),
 
+    #[error("Unrecognized attachment method {0}")]
+    BadAttachmentMethod(u32),
+
     #[error("Unrecognized entry type {0}")]
     BadEntryType(u32),
 
     #[error("Unrecognized value type {0}")]
     BadValueType(u32),
 
+    #[error("Unrecognized named property type {0}")]
+    BadNamedPropertyType(u8),
+
+    #[error("Unrecognized file content type {0}")]
+    BadContentType(u8),
+
+    #[error("Unrecognized file format {0}")]
+    BadFileFormat(u8),
+
+    #[error("Unrecognized encryption type {0}")]
+    BadEncryptionType(u8),
+
+    #[error("Unrecognized flag status {0}")]
+    BadFlagStatus(u32),
+
+    #[error("Unrecognized busy status {0}")]
+    BadBusyStatus(u32),
+
+    #[error("Unrecognized task status {0}")]
+    BadTaskStatus(u32),
+
+    #[error("Value data doesn't match value type {0:?}")]
+    BadValueData(ValueType),
+
+    #[error("Compressed RTF is corrupt: {0}")]
+    BadCompressedRtf(&'static str),
+
     #[error("Bad UUID")]
     BadUuid(#[from] uuid::Error),
 
     #[error("Item is not a folder.")]
     NotAFolder,
 
+    #[error("Item is not a contact.")]
+    NotAContact,
+
+    #[error("Item is not a distribution list.")]
+    NotADistributionList,
+
+    #[error("Item is not an appointment.")]
+    NotAnAppointment,
+
+    #[error("Item is not a task.")]
+    NotATask,
+
     #[error("Codepage {0} is not supported.")]
     BadCodePage(u32),
+
+    #[error("Bad item ID path \"{0}\", expected '_' delimited numbers.")]
+    BadItemIdPath(String),
+
+    #[error("Item {0} was not found.")]
+    ItemNotFound(String),
 }
 
 impl Error {
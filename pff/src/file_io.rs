@@ -0,0 +1,192 @@
+use std::{
+    io::{ErrorKind, Read, Seek, SeekFrom},
+    ptr, slice,
+};
+
+use pff_sys::{libbfio_error_t, libbfio_handle_free, libbfio_handle_initialize, libbfio_handle_t};
+
+use crate::{error::Error, recordset::Seek as Whence};
+
+/// Tells libbfio that it owns the IO handle and must call our free
+/// callback when the libbfio handle is freed.
+const IO_HANDLE_MANAGED: u8 = 0x01;
+
+pub(crate) trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+type BoxedReader = Box<dyn ReadSeek>;
+
+/// A libbfio file IO handle that routes all reads and seeks to a Rust
+/// reader.
+#[derive(Debug)]
+pub(crate) struct FileIoHandle {
+    handle: *mut libbfio_handle_t,
+}
+
+impl FileIoHandle {
+    pub(crate) fn new<R: Read + Seek + Send + 'static>(reader: R) -> Result<Self, Error> {
+        // the reader is double boxed so that we have a thin pointer to pass
+        // through to libbfio as the IO handle
+        let reader: Box<BoxedReader> = Box::new(Box::new(reader));
+        let io_handle = Box::into_raw(reader) as *mut isize;
+        let mut handle: *mut libbfio_handle_t = ptr::null_mut();
+
+        let res = unsafe {
+            libbfio_handle_initialize(
+                &mut handle,
+                io_handle,
+                Some(free_io_handle),
+                None,
+                Some(open_io_handle),
+                Some(close_io_handle),
+                Some(read_io_handle),
+                Some(write_io_handle),
+                Some(seek_io_handle),
+                Some(exists_io_handle),
+                Some(is_open_io_handle),
+                Some(get_size_io_handle),
+                IO_HANDLE_MANAGED,
+                ptr::null_mut(),
+            )
+        };
+
+        match res {
+            1 => Ok(FileIoHandle { handle }),
+            _ => {
+                // libbfio did not take ownership of the reader so we drop it
+                drop(unsafe { Box::from_raw(io_handle as *mut BoxedReader) });
+                Err(Error::FileIoHandle)
+            }
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut libbfio_handle_t {
+        self.handle
+    }
+}
+
+impl Drop for FileIoHandle {
+    fn drop(&mut self) {
+        unsafe { libbfio_handle_free(&mut self.handle, ptr::null_mut()) };
+    }
+}
+
+unsafe fn reader<'a>(io_handle: *mut isize) -> &'a mut BoxedReader {
+    &mut *(io_handle as *mut BoxedReader)
+}
+
+unsafe extern "C" fn free_io_handle(
+    io_handle: *mut *mut isize,
+    _error: *mut *mut libbfio_error_t,
+) -> i32 {
+    if !(*io_handle).is_null() {
+        drop(Box::from_raw(*io_handle as *mut BoxedReader));
+        *io_handle = ptr::null_mut();
+    }
+    1
+}
+
+unsafe extern "C" fn open_io_handle(
+    _io_handle: *mut isize,
+    _access_flags: i32,
+    _error: *mut *mut libbfio_error_t,
+) -> i32 {
+    1
+}
+
+unsafe extern "C" fn close_io_handle(
+    _io_handle: *mut isize,
+    _error: *mut *mut libbfio_error_t,
+) -> i32 {
+    0
+}
+
+unsafe extern "C" fn read_io_handle(
+    io_handle: *mut isize,
+    buffer: *mut u8,
+    size: usize,
+    _error: *mut *mut libbfio_error_t,
+) -> isize {
+    let reader = reader(io_handle);
+    let buf = slice::from_raw_parts_mut(buffer, size);
+
+    // libpff treats short reads as failures so keep reading till the buffer
+    // is full or we hit the end of the stream
+    let mut read = 0;
+    while read < size {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(count) => read += count,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => return -1,
+        }
+    }
+
+    read as isize
+}
+
+unsafe extern "C" fn write_io_handle(
+    _io_handle: *mut isize,
+    _buffer: *const u8,
+    _size: usize,
+    _error: *mut *mut libbfio_error_t,
+) -> isize {
+    -1
+}
+
+unsafe extern "C" fn seek_io_handle(
+    io_handle: *mut isize,
+    offset: i64,
+    whence: i32,
+    _error: *mut *mut libbfio_error_t,
+) -> i64 {
+    let pos = match Whence::try_from(whence) {
+        Ok(Whence::Set) if offset >= 0 => SeekFrom::Start(offset as u64),
+        Ok(Whence::Current) => SeekFrom::Current(offset),
+        Ok(Whence::End) => SeekFrom::End(offset),
+        _ => return -1,
+    };
+
+    match reader(io_handle).seek(pos) {
+        Ok(pos) => pos as i64,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn exists_io_handle(
+    _io_handle: *mut isize,
+    _error: *mut *mut libbfio_error_t,
+) -> i32 {
+    1
+}
+
+unsafe extern "C" fn is_open_io_handle(
+    _io_handle: *mut isize,
+    _error: *mut *mut libbfio_error_t,
+) -> i32 {
+    1
+}
+
+unsafe extern "C" fn get_size_io_handle(
+    io_handle: *mut isize,
+    size: *mut u64,
+    _error: *mut *mut libbfio_error_t,
+) -> i32 {
+    let reader = reader(io_handle);
+
+    // find the size by seeking to the end and then restore the position
+    let size_res = reader.stream_position().and_then(|pos| {
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(pos))?;
+        Ok(end)
+    });
+
+    match size_res {
+        Ok(end) => {
+            *size = end;
+            1
+        }
+        Err(_) => -1,
+    }
+}
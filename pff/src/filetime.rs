@@ -1,4 +1,6 @@
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime};
+
+use crate::{error::Error, item::ValueType};
 
 const HECTONANOSECS_IN_SEC: i64 = 10_000_000;
 const HECTONANOSEC_TO_UNIX_EPOCH: i64 = 11_644_473_600 * HECTONANOSECS_IN_SEC;
@@ -22,3 +24,29 @@ impl From<FileTime> for NaiveDateTime {
         ft.filetime_to_naive_dt()
     }
 }
+
+/// Returns the day `time` is nearest to. Outlook stores dates without a
+/// time, for e.g. birthdays and all day events, as midnight local time
+/// converted to UTC, which can fall on the day before. Rounding to the
+/// nearest day gets the local date back for any time zone.
+pub fn nearest_date(time: NaiveDateTime) -> NaiveDate {
+    (time + Duration::hours(12)).date()
+}
+
+/// An OLE automation date, i.e., the number of days since 1899-12-30 with
+/// the time of day as the fractional part.
+pub(crate) struct FloatingTime(pub(crate) f64);
+
+impl TryFrom<FloatingTime> for NaiveDateTime {
+    type Error = Error;
+
+    fn try_from(ft: FloatingTime) -> Result<Self, Self::Error> {
+        let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .expect("OLE automation epoch is a valid date");
+        // the value comes from the file so it can be anything
+        Duration::try_milliseconds((ft.0 * 86_400_000.0).round() as i64)
+            .and_then(|offset| epoch.checked_add_signed(offset))
+            .ok_or(Error::BadValueData(ValueType::Floatingtime))
+    }
+}
@@ -9,7 +9,7 @@ use pff_sys::{
 
 use crate::{
     error::Error,
-    item::{Item, ItemExt, PffItem},
+    item::{EntryType, Item, ItemExt, PffItem},
     message::Message,
 };
 
@@ -56,12 +56,36 @@ impl Folder {
         }
     }
 
+    /// The kind of items the folder holds, for e.g. `IPF.Note` for mail or
+    /// `IPF.Contact` for contacts.
+    pub fn container_class(&self) -> Result<Option<String>, Error> {
+        self.get_string_value(EntryType::ContainerClass)
+    }
+
+    /// Number of items in the folder as recorded in the folder's properties.
+    pub fn content_count(&self) -> Result<Option<u32>, Error> {
+        self.get_u32(EntryType::NumberOfContentItems)
+    }
+
+    /// Number of unread items in the folder as recorded in the folder's
+    /// properties.
+    pub fn unread_count(&self) -> Result<Option<u32>, Error> {
+        self.get_u32(EntryType::NumberOfUnreadContentItems)
+    }
+
+    /// Follows `id_path` down from this folder. Returns `None` if an item
+    /// on the path doesn't exist or the path is empty.
     pub fn get_item_from_id_path(&self, id_path: &[u32]) -> Result<Option<PffItem>, Error> {
-        let mut cur = self.sub_item_by_id::<PffItem>(id_path[0])?;
-        let mut index = 1;
-        while let (Some(si), true) = (cur.as_ref(), (index < id_path.len())) {
-            cur = si.sub_item_by_id(id_path[index])?;
-            index += 1;
+        let Some((first, rest)) = id_path.split_first() else {
+            return Ok(None);
+        };
+
+        let mut cur = self.sub_item_by_id::<PffItem>(*first)?;
+        for id in rest {
+            cur = match cur {
+                Some(item) => item.sub_item_by_id(*id)?,
+                None => break,
+            };
         }
 
         Ok(cur)
@@ -83,6 +107,19 @@ impl Folder {
         SubFoldersIterator::new(self)
     }
 
+    /// The sub folder at `index`, from 0 to [`Folder::sub_folders_count`].
+    pub fn sub_folder(&self, index: i32) -> Result<Folder, Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut sub_item: *mut libpff_item_t = ptr::null_mut();
+        let res =
+            unsafe { libpff_folder_get_sub_folder(self.item(), index, &mut sub_item, &mut error) };
+
+        match res {
+            1 => Ok(Folder::new(sub_item)),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+
     pub fn messages_count(&self) -> Result<i32, Error> {
         let mut count: i32 = 0;
         let mut error: *mut libpff_error_t = ptr::null_mut();
@@ -100,6 +137,19 @@ impl Folder {
         SubMessagesIterator::new(self)
     }
 
+    /// The message at `index`, from 0 to [`Folder::messages_count`].
+    pub fn message(&self, index: i32) -> Result<Message, Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut sub_item: *mut libpff_item_t = ptr::null_mut();
+        let res =
+            unsafe { libpff_folder_get_sub_message(self.item(), index, &mut sub_item, &mut error) };
+
+        match res {
+            1 => Ok(Message::new(sub_item)),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+
     fn get_name_size(&self) -> Result<Option<usize>, Error> {
         let mut error: *mut libpff_error_t = ptr::null_mut();
         let mut name_size = 0;
@@ -157,24 +207,11 @@ impl<'a> Iterator for SubFoldersIterator<'a> {
         if self.index >= self.count {
             None
         } else {
-            let mut error: *mut libpff_error_t = ptr::null_mut();
-            let mut sub_item: *mut libpff_item_t = ptr::null_mut();
-            let res = unsafe {
-                libpff_folder_get_sub_folder(
-                    self.item.item(),
-                    self.index,
-                    &mut sub_item,
-                    &mut error,
-                )
-            };
-
-            match res {
-                1 => {
-                    self.index += 1;
-                    Some(Ok(Folder::new(sub_item)))
-                }
-                _ => Some(Err(Error::pff_error(error))),
+            let sub_folder = self.item.sub_folder(self.index);
+            if sub_folder.is_ok() {
+                self.index += 1;
             }
+            Some(sub_folder)
         }
     }
 }
@@ -202,24 +239,11 @@ impl<'a> Iterator for SubMessagesIterator<'a> {
         if self.index >= self.count {
             None
         } else {
-            let mut error: *mut libpff_error_t = ptr::null_mut();
-            let mut sub_item: *mut libpff_item_t = ptr::null_mut();
-            let res = unsafe {
-                libpff_folder_get_sub_message(
-                    self.item.item(),
-                    self.index,
-                    &mut sub_item,
-                    &mut error,
-                )
-            };
-
-            match res {
-                1 => {
-                    self.index += 1;
-                    Some(Ok(Message::new(sub_item)))
-                }
-                _ => Some(Err(Error::pff_error(error))),
+            let message = self.item.message(self.index);
+            if message.is_ok() {
+                self.index += 1;
             }
+            Some(message)
         }
     }
 }
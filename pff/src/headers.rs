@@ -0,0 +1,283 @@
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use chrono::{DateTime, FixedOffset};
+use encoding_rs::{Encoding, UTF_8};
+
+/// Encoded words are often missing their base64 padding so we accept both.
+const ENCODED_WORD_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// The header fields of an RFC 5322 message, for e.g. the transport headers
+/// stored with a message. Folded fields are unfolded and RFC 2047 encoded
+/// words are decoded. Fields keep the order they appear in.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Headers {
+    fields: Vec<(String, String)>,
+}
+
+impl Headers {
+    pub fn parse(raw: &str) -> Self {
+        let mut fields: Vec<(String, String)> = vec![];
+
+        for line in raw.lines() {
+            // the header block ends at the first empty line
+            if line.trim().is_empty() {
+                break;
+            }
+
+            // lines starting with whitespace continue the previous field
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push_str(line);
+                }
+                continue;
+            }
+
+            // lines that aren't fields at all (for e.g. an mbox "From "
+            // line) are skipped
+            if let Some((name, value)) = line.split_once(':') {
+                let name = name.trim();
+                if !name.is_empty() && !name.contains(' ') {
+                    fields.push((name.to_string(), value.to_string()));
+                }
+            }
+        }
+
+        Headers {
+            fields: fields
+                .into_iter()
+                .map(|(name, value)| (name, decode_encoded_words(value.trim())))
+                .collect(),
+        }
+    }
+
+    /// All fields as `(name, value)` pairs in the order they appear in.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// The value of the first field called `name`. Field names are case
+    /// insensitive.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The values of all the fields called `name`, for fields that can be
+    /// repeated such as `Received`.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.fields
+            .iter()
+            .filter(move |(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The message ID without the angle brackets.
+    pub fn message_id(&self) -> Option<String> {
+        self.get("Message-ID")
+            .and_then(|value| parse_message_ids(value).into_iter().next())
+    }
+
+    pub fn in_reply_to(&self) -> Vec<String> {
+        self.get("In-Reply-To")
+            .map(parse_message_ids)
+            .unwrap_or_default()
+    }
+
+    /// The message IDs of the earlier messages in the thread, oldest first.
+    pub fn references(&self) -> Vec<String> {
+        self.get_all("References")
+            .flat_map(parse_message_ids)
+            .collect()
+    }
+
+    /// The `Received` fields, starting with the one added by the last hop.
+    pub fn received(&self) -> Vec<&str> {
+        self.get_all("Received").collect()
+    }
+
+    pub fn list_id(&self) -> Option<&str> {
+        self.get("List-Id")
+    }
+
+    pub fn authentication_results(&self) -> Vec<&str> {
+        self.get_all("Authentication-Results").collect()
+    }
+
+    pub fn subject(&self) -> Option<&str> {
+        self.get("Subject")
+    }
+
+    pub fn date(&self) -> Option<DateTime<FixedOffset>> {
+        self.get("Date")
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+    }
+}
+
+/// Parses the `<id>` message IDs in a field such as `References` and
+/// returns them without the angle brackets.
+fn parse_message_ids(value: &str) -> Vec<String> {
+    let mut ids = vec![];
+    let mut rest = value;
+
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        let id = rest[start + 1..start + len].trim();
+        if !id.is_empty() {
+            ids.push(id.to_string());
+        }
+        rest = &rest[start + len + 1..];
+    }
+
+    // some mailers leave out the angle brackets
+    if ids.is_empty() {
+        ids = value.split_whitespace().map(str::to_string).collect();
+    }
+
+    ids
+}
+
+/// Decodes the RFC 2047 encoded words (`=?charset?encoding?text?=`) in a
+/// field value. Whitespace between adjacent encoded words is dropped.
+pub(crate) fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    let mut last_was_encoded = false;
+
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+
+        match parse_encoded_word(candidate) {
+            Some((decoded, len)) => {
+                if !(last_was_encoded && before.trim().is_empty()) {
+                    out.push_str(before);
+                }
+                out.push_str(&decoded);
+                rest = &candidate[len..];
+                last_was_encoded = true;
+            }
+            None => {
+                out.push_str(before);
+                out.push_str("=?");
+                rest = &candidate[2..];
+                last_was_encoded = false;
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
+
+/// Decodes the encoded word at the start of `value` and returns the decoded
+/// text along with the length of the encoded word.
+fn parse_encoded_word(value: &str) -> Option<(String, usize)> {
+    let mut parts = value[2..].splitn(3, '?');
+    let charset = parts.next()?;
+    let encoding = parts.next()?;
+    let rest = parts.next()?;
+    let text_len = rest.find("?=")?;
+    let text = &rest[..text_len];
+    // "=?" charset "?" encoding "?" text "?="
+    let len = charset.len() + encoding.len() + text_len + 6;
+
+    // encoded words can't contain whitespace
+    if text.contains(char::is_whitespace) || charset.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let bytes = match encoding {
+        "B" | "b" => ENCODED_WORD_BASE64.decode(text).ok()?,
+        "Q" | "q" => decode_q(text)?,
+        _ => return None,
+    };
+
+    // RFC 2231 allows a language to follow the charset, for e.g. "utf-8*en"
+    let charset = charset.split('*').next().unwrap_or_default();
+    let encoding = Encoding::for_label(charset.as_bytes()).unwrap_or(UTF_8);
+    let (decoded, _) = encoding.decode_without_bom_handling(&bytes);
+
+    Some((decoded.into_owned(), len))
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'_' => out.push(b' '),
+            b'=' => {
+                let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 2;
+            }
+            byte => out.push(byte),
+        }
+        index += 1;
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::headers::{decode_encoded_words, Headers};
+
+    const RAW: &str = "Received: from mail.example.com (mail.example.com [192.0.2.1])\r\n\
+\tby mx.example.org; Tue, 1 Mar 2022 10:00:00 +0000\r\n\
+Received: from client (client [198.51.100.7])\r\n\
+\tby mail.example.com; Tue, 1 Mar 2022 09:59:58 +0000\r\n\
+Subject: =?utf-8?B?R3LDvMOfZQ==?= =?utf-8?Q?_aus_K=C3=B6ln?=\r\n\
+Message-ID: <abc@example.com>\r\n\
+In-Reply-To: <parent@example.com>\r\n\
+References: <root@example.com>\r\n\
+\x20<parent@example.com>\r\n\
+List-Id: Team <team.example.com>\r\n\
+Date: Tue, 1 Mar 2022 09:59:57 +0000\r\n\
+\r\n\
+Body: not a header\r\n";
+
+    #[test]
+    fn parse_fields() {
+        let headers = Headers::parse(RAW);
+
+        assert_eq!(headers.subject().unwrap(), "Grüße aus Köln");
+        assert_eq!(headers.message_id().unwrap(), "abc@example.com");
+        assert_eq!(headers.in_reply_to(), vec!["parent@example.com"]);
+        assert_eq!(
+            headers.references(),
+            vec!["root@example.com", "parent@example.com"]
+        );
+        assert_eq!(headers.list_id().unwrap(), "Team <team.example.com>");
+        assert_eq!(headers.received().len(), 2);
+        assert!(headers.received()[0].ends_with("+0000"));
+        assert!(headers.received()[0].contains("\tby mx.example.org"));
+        assert!(headers.date().is_some());
+        assert!(headers.get("body").is_none());
+        assert_eq!(headers.get("message-id"), headers.get("Message-ID"));
+    }
+
+    #[test]
+    fn encoded_words() {
+        assert_eq!(decode_encoded_words("plain text"), "plain text");
+        assert_eq!(
+            decode_encoded_words("=?iso-8859-1?q?caf=E9?= au lait"),
+            "café au lait"
+        );
+        assert_eq!(decode_encoded_words("a =?bogus"), "a =?bogus");
+        assert_eq!(decode_encoded_words("=?utf-8?B?w7w?="), "ü");
+    }
+}
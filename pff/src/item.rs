@@ -1,6 +1,7 @@
 use std::ptr;
 
 use bitflags::bitflags;
+use chrono::{NaiveDate, NaiveDateTime};
 use itertools::Itertools;
 use num_enum::{FromPrimitive, IntoPrimitive, TryFromPrimitive};
 use pff_sys::{
@@ -13,10 +14,17 @@ use pff_sys::{
 };
 
 use crate::{
+    appointment::Appointment,
+    contact::Contact,
+    distributionlist::DistributionList,
     encoding,
     error::Error,
+    filetime,
     folder::Folder,
+    message::Message,
+    namedproperty::NamedProperty,
     recordset::{RecordEntry, RecordSet},
+    task::Task,
 };
 
 pub trait Item {
@@ -74,6 +82,24 @@ pub trait ItemExt: Item + Sized {
             .unwrap_or(Ok(None))
     }
 
+    /// Looks up an entry by its property set GUID and name, for e.g.,
+    /// `NamedProperty::new(property_set::PS_PUBLIC_STRINGS, "Keywords")`.
+    fn entry_by_named_property(
+        &self,
+        property: &NamedProperty,
+    ) -> Result<Option<RecordEntry>, Error> {
+        for record_set in self.record_sets()? {
+            for entry in record_set?.entries()? {
+                let entry = entry?;
+                if entry.named_property()?.as_ref() == Some(property) {
+                    return Ok(Some(entry));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
     fn entries_count(&self) -> Result<u32, Error> {
         let mut count: u32 = 0;
         let mut error: *mut libpff_error_t = ptr::null_mut();
@@ -173,12 +199,107 @@ pub trait ItemExt: Item + Sized {
         }
     }
 
+    /// Reads a string entry, treating an empty string as a missing one.
+    fn get_string_value(&self, entry_type: EntryType) -> Result<Option<String>, Error> {
+        match self.get_string_size(entry_type)? {
+            Some(str_size) if str_size > 0 => self.get_string(entry_type, str_size),
+            _ => Ok(None),
+        }
+    }
+
+    fn get_u32(&self, entry_type: EntryType) -> Result<Option<u32>, Error> {
+        self.first_entry_by_type(entry_type)?
+            .map(|entry| entry.as_u32())
+            .transpose()
+    }
+
+    fn get_bool(&self, entry_type: EntryType) -> Result<Option<bool>, Error> {
+        self.first_entry_by_type(entry_type)?
+            .map(|entry| entry.as_bool())
+            .transpose()
+    }
+
+    fn get_time(&self, entry_type: EntryType) -> Result<Option<NaiveDateTime>, Error> {
+        self.first_entry_by_type(entry_type)?
+            .map(|entry| entry.as_filetime())
+            .transpose()
+    }
+
+    /// Reads a time entry that holds a date, see [`filetime::nearest_date`].
+    fn get_date(&self, entry_type: EntryType) -> Result<Option<NaiveDate>, Error> {
+        Ok(self.get_time(entry_type)?.map(filetime::nearest_date))
+    }
+
+    /// Converts the item into the typed item for its kind. The message class
+    /// decides for messages since libpff only recognizes the standard
+    /// classes, the item type is the fallback for messages without one.
+    fn classify(self) -> Result<AnyItem, Error> {
+        let item_type = match self.type_()? {
+            ItemType::Folder => ItemType::Folder,
+            item_type => self
+                .get_string_value(EntryType::MessageClass)?
+                .as_deref()
+                .and_then(item_type_from_message_class)
+                .unwrap_or(item_type),
+        };
+
+        let item = self.detach();
+        Ok(match item_type {
+            ItemType::Folder => AnyItem::Folder(Folder::new(item)),
+            ItemType::Email
+            | ItemType::EmailSmime
+            | ItemType::Meeting
+            | ItemType::PostingNote
+            | ItemType::TaskRequest
+            | ItemType::Sharing
+            | ItemType::Fax
+            | ItemType::Voicemail
+            | ItemType::Sms
+            | ItemType::Mms
+            | ItemType::RssFeed => AnyItem::Email(Message::new(item)),
+            ItemType::Contact => AnyItem::Contact(Contact::new(item)),
+            ItemType::Appointment => AnyItem::Appointment(Appointment::new(item)),
+            ItemType::Task => AnyItem::Task(Task::new(item)),
+            ItemType::Note => AnyItem::Note(Message::new(item)),
+            ItemType::DistributionList => AnyItem::DistributionList(DistributionList::new(item)),
+            _ => AnyItem::Other(PffItem::new(item)),
+        })
+    }
+
     fn into_folder(self) -> Result<Folder, Error> {
         match self.type_()? {
             ItemType::Folder => Ok(Folder::new(self.detach())),
             _ => Err(Error::NotAFolder),
         }
     }
+
+    fn into_appointment(self) -> Result<Appointment, Error> {
+        match self.type_()? {
+            ItemType::Appointment => Ok(Appointment::new(self.detach())),
+            _ => Err(Error::NotAnAppointment),
+        }
+    }
+
+    fn into_contact(self) -> Result<Contact, Error> {
+        match self.type_()? {
+            ItemType::Contact => Ok(Contact::new(self.detach())),
+            _ => Err(Error::NotAContact),
+        }
+    }
+
+    fn into_distribution_list(self) -> Result<DistributionList, Error> {
+        match self.type_()? {
+            ItemType::DistributionList => Ok(DistributionList::new(self.detach())),
+            _ => Err(Error::NotADistributionList),
+        }
+    }
+
+    fn into_task(self) -> Result<Task, Error> {
+        match self.type_()? {
+            ItemType::Task => Ok(Task::new(self.detach())),
+            _ => Err(Error::NotATask),
+        }
+    }
 }
 
 /// Blanket impl of `ItemExt` for all `T`s that implement `Item`.
@@ -310,6 +431,119 @@ impl PffItem {
     }
 }
 
+/// An item converted into the typed item for its kind by
+/// [`ItemExt::classify`].
+#[derive(Debug)]
+pub enum AnyItem {
+    Folder(Folder),
+    /// Anything that is delivered like a mail, including meeting requests
+    /// and responses (`IPM.Schedule.Meeting.*`), task requests, posts and
+    /// reports.
+    Email(Message),
+    Contact(Contact),
+    Appointment(Appointment),
+    Task(Task),
+    /// A sticky note (`IPM.StickyNote`), not to be confused with
+    /// `IPM.Note`, the class of mails.
+    Note(Message),
+    DistributionList(DistributionList),
+    Other(PffItem),
+}
+
+/// The kinds of [`AnyItem`], for e.g. to filter items by.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ItemKind {
+    Folder,
+    Email,
+    Contact,
+    Appointment,
+    Task,
+    Note,
+    DistributionList,
+    Other,
+}
+
+impl AnyItem {
+    pub fn kind(&self) -> ItemKind {
+        match self {
+            AnyItem::Folder(_) => ItemKind::Folder,
+            AnyItem::Email(_) => ItemKind::Email,
+            AnyItem::Contact(_) => ItemKind::Contact,
+            AnyItem::Appointment(_) => ItemKind::Appointment,
+            AnyItem::Task(_) => ItemKind::Task,
+            AnyItem::Note(_) => ItemKind::Note,
+            AnyItem::DistributionList(_) => ItemKind::DistributionList,
+            AnyItem::Other(_) => ItemKind::Other,
+        }
+    }
+
+    /// Every item but a folder is a message, this returns it as one for
+    /// e.g. to read the properties all messages share. Returns `None` for
+    /// folders.
+    pub fn into_message(self) -> Option<Message> {
+        let item = match self {
+            AnyItem::Folder(_) => return None,
+            AnyItem::Email(item) | AnyItem::Note(item) => return Some(item),
+            AnyItem::Contact(item) => item.detach(),
+            AnyItem::Appointment(item) => item.detach(),
+            AnyItem::Task(item) => item.detach(),
+            AnyItem::DistributionList(item) => item.detach(),
+            AnyItem::Other(item) => item.detach(),
+        };
+
+        Some(Message::new(item))
+    }
+
+    pub fn id(&self) -> Result<u32, Error> {
+        match self {
+            AnyItem::Folder(item) => item.id(),
+            AnyItem::Email(item) | AnyItem::Note(item) => item.id(),
+            AnyItem::Contact(item) => item.id(),
+            AnyItem::Appointment(item) => item.id(),
+            AnyItem::Task(item) => item.id(),
+            AnyItem::DistributionList(item) => item.id(),
+            AnyItem::Other(item) => item.id(),
+        }
+    }
+}
+
+/// Maps a message class to the item type of the typed item for it. Classes
+/// are hierarchical, so `IPM.Contact.Custom` is a contact too. Returns
+/// `None` for classes we don't know.
+fn item_type_from_message_class(message_class: &str) -> Option<ItemType> {
+    let message_class = message_class.to_ascii_lowercase();
+    let is = |class: &str| {
+        message_class == class
+            || message_class
+                .strip_prefix(class)
+                .is_some_and(|rest| rest.starts_with('.'))
+    };
+
+    // IPM.TaskRequest has to come before IPM.Task, it's a mail about a task
+    if is("ipm.note")
+        || is("ipm.schedule")
+        || is("ipm.taskrequest")
+        || is("ipm.post")
+        || is("ipm.sharing")
+        || is("ipm.outlook.recall")
+        || is("report")
+    {
+        Some(ItemType::Email)
+    } else if is("ipm.contact") {
+        Some(ItemType::Contact)
+    } else if is("ipm.distlist") {
+        Some(ItemType::DistributionList)
+    } else if is("ipm.appointment") {
+        Some(ItemType::Appointment)
+    } else if is("ipm.task") {
+        Some(ItemType::Task)
+    } else if is("ipm.stickynote") {
+        Some(ItemType::Note)
+    } else {
+        None
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
 #[repr(u32)]
 pub enum ValueType {
@@ -347,6 +581,20 @@ pub enum ValueType {
     MultiValueBinaryData = 0x1102,
 }
 
+impl ValueType {
+    const MULTI_VALUE_FLAG: u32 = 0x1000;
+
+    pub fn is_multi_value(&self) -> bool {
+        u32::from(*self) & Self::MULTI_VALUE_FLAG != 0
+    }
+
+    /// Returns the type of the individual values of a multi-value type and
+    /// the type itself otherwise.
+    pub fn element_type(&self) -> ValueType {
+        ValueType::try_from(u32::from(*self) & !Self::MULTI_VALUE_FLAG).unwrap_or(*self)
+    }
+}
+
 bitflags! {
     pub struct ValueFlags: u8 {
         const MATCH_ANY_VALUE_TYPE = 0x01;
@@ -399,9 +647,12 @@ pub enum EntryType {
     MessagePermission = 0x0e27,
     MessageUrlComputerNameSet = 0x0e62,
     MessageTrustSender = 0x0e79,
+    EntryIdentifier = 0x0fff,
     MessageBodyPlainText = 0x1000,
     MessageBodyCompressedRtf = 0x1009,
     MessageBodyHtml = 0x1013,
+    MessageInternetMessageId = 0x1035,
+    MessageFlagStatus = 0x1090,
     EmailEmlFilename = 0x10f3,
     DisplayName = 0x3001,
     AddressType = 0x3002,
@@ -418,10 +669,15 @@ pub enum EntryType {
     ContainerClass = 0x3613,
     NumberOfAssociatedContent = 0x3617,
     AttachmentDataObject = 0x3701,
+    AttachmentExtension = 0x3703,
     AttachmentFilenameShort = 0x3704,
     AttachmentMethod = 0x3705,
     AttachmentFilenameLong = 0x3707,
     AttachmentRenderingPosition = 0x370b,
+    AttachmentMimeTag = 0x370e,
+    AttachmentContentId = 0x3712,
+    AttachmentContentLocation = 0x3713,
+    AttachmentFlags = 0x3714,
     ContactCallbackPhoneNumber = 0x3a02,
     ContactGenerationalAbbreviation = 0x3a05,
     ContactGivenName = 0x3a06,
@@ -440,27 +696,46 @@ pub enum EntryType {
     ContactBusinessFaxNumber = 0x3a24,
     ContactCountry = 0x3a26,
     ContactLocality = 0x3a27,
+    ContactStateOrProvince = 0x3a28,
+    ContactStreetAddress = 0x3a29,
+    ContactPostalCode = 0x3a2a,
+    ContactBirthday = 0x3a42,
     ContactTitle = 0x3a45,
+    ContactNickname = 0x3a4f,
+    ContactBusinessHomePage = 0x3a51,
     MessageBodyCodepage = 0x3fde,
     MessageCodepage = 0x3ffd,
+    MessageSenderSmtpAddress = 0x5d01,
+    MessageSentRepresentingSmtpAddress = 0x5d02,
+    MessageReceivedBySmtpAddress = 0x5d07,
     RecipientDisplayName = 0x5ff6,
+    RecipientEntryIdentifier = 0x5ff7,
     FolderChildCount = 0x6638,
     SubItemIdentifier = 0x67f2,
     MessageStorePasswordChecksum = 0x67ff,
+    AttachmentHidden = 0x7ffe,
     AddressFileUnder = 0x8005,
     DistributionListName = 0x8053,
     DistributionListMemberOneOffEntryIdentifiers = 0x8054,
     DistributionListMemberEntryIdentifiers = 0x8055,
+    ContactEmailAddressType1 = 0x8082,
     ContactEmailAddress1 = 0x8083,
+    ContactEmailEntryIdentifier1 = 0x8085,
+    ContactEmailAddressType2 = 0x8092,
     ContactEmailAddress2 = 0x8093,
+    ContactEmailEntryIdentifier2 = 0x8095,
+    ContactEmailAddressType3 = 0x80a2,
     ContactEmailAddress3 = 0x80a3,
+    ContactEmailEntryIdentifier3 = 0x80a5,
     TaskStatus = 0x8101,
     TaskPercentageComplete = 0x8102,
     TaskStartDate = 0x8104,
     TaskDueDate = 0x8105,
+    TaskDateCompleted = 0x810f,
     TaskActualEffort = 0x8110,
     TaskTotalEffort = 0x8111,
     TaskVersion = 0x8112,
+    TaskRecurrenceData = 0x8116,
     TaskIsComplete = 0x811c,
     TaskIsRecurring = 0x8126,
     AppointmentBusyStatus = 0x8205,
@@ -468,8 +743,11 @@ pub enum EntryType {
     AppointmentStartTime = 0x820d,
     AppointmentEndTime = 0x820e,
     AppointmentDuration = 0x8213,
+    AppointmentSubType = 0x8215,
+    AppointmentRecurrenceData = 0x8216,
     AppointmentIsRecurring = 0x8223,
     AppointmentRecurrencePattern = 0x8232,
+    AppointmentTimezoneStruct = 0x8233,
     AppointmentTimezoneDescription = 0x8234,
     AppointmentFirstEffectiveTime = 0x8235,
     AppointmentLastEffectiveTime = 0x8236,
@@ -518,7 +796,10 @@ pub enum ItemType {
 
 #[cfg(test)]
 mod tests {
-    use crate::{item::ItemExt, FileOpenFlags, Pff};
+    use crate::{
+        item::{item_type_from_message_class, AnyItem, ItemExt, ItemType},
+        FileOpenFlags, Pff,
+    };
 
     const TEST_PST_FILE: &str = "../data/sample.ost";
 
@@ -556,4 +837,38 @@ mod tests {
             println!("{:?}", i.unwrap().type_().unwrap());
         }
     }
+
+    #[test]
+    fn classify() {
+        let pff = Pff::new().unwrap();
+        let pff = pff.open(TEST_PST_FILE, FileOpenFlags::READ).unwrap();
+        let item = pff.root_folder().unwrap().unwrap();
+        assert!(matches!(item.classify().unwrap(), AnyItem::Folder(_)));
+    }
+
+    #[test]
+    fn message_class() {
+        let cases = [
+            ("IPM.Note", Some(ItemType::Email)),
+            ("IPM.Note.SMIME.MultipartSigned", Some(ItemType::Email)),
+            ("IPM.Schedule.Meeting.Request", Some(ItemType::Email)),
+            ("IPM.Schedule.Meeting.Resp.Pos", Some(ItemType::Email)),
+            ("REPORT.IPM.Note.NDR", Some(ItemType::Email)),
+            ("IPM.TaskRequest.Accept", Some(ItemType::Email)),
+            ("ipm.contact", Some(ItemType::Contact)),
+            ("IPM.DistList", Some(ItemType::DistributionList)),
+            ("IPM.Appointment", Some(ItemType::Appointment)),
+            ("IPM.Task", Some(ItemType::Task)),
+            ("IPM.StickyNote", Some(ItemType::Note)),
+            ("IPM.Notes", None),
+            ("IPM.Activity", None),
+        ];
+        for (message_class, item_type) in cases {
+            assert_eq!(
+                item_type_from_message_class(message_class),
+                item_type,
+                "{message_class}"
+            );
+        }
+    }
 }
@@ -1,24 +1,52 @@
-use std::{ffi::CString, ptr};
+use std::{
+    ffi::{CStr, CString},
+    fmt::Display,
+    io::{Read, Seek},
+    path::Path,
+    ptr,
+};
 
 use bitflags::bitflags;
 use item::Item;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use pff_sys::{
-    libpff_error_t, libpff_file_close, libpff_file_free, libpff_file_get_root_folder,
-    libpff_file_get_root_item, libpff_file_get_size, libpff_file_initialize, libpff_file_open,
-    libpff_file_t, libpff_item_t, LIBPFF_ACCESS_FLAGS_LIBPFF_ACCESS_FLAG_READ,
-    LIBPFF_ACCESS_FLAGS_LIBPFF_ACCESS_FLAG_WRITE,
+    libpff_error_t, libpff_file_close, libpff_file_free, libpff_file_get_content_type,
+    libpff_file_get_encryption_type, libpff_file_get_item_by_identifier,
+    libpff_file_get_number_of_orphan_items, libpff_file_get_number_of_recovered_items,
+    libpff_file_get_orphan_item_by_index, libpff_file_get_recovered_item_by_index,
+    libpff_file_get_root_folder, libpff_file_get_root_item, libpff_file_get_size,
+    libpff_file_get_type, libpff_file_initialize, libpff_file_open,
+    libpff_file_open_file_io_handle, libpff_file_recover_items, libpff_file_t, libpff_item_t,
+    LIBPFF_ACCESS_FLAGS_LIBPFF_ACCESS_FLAG_READ, LIBPFF_ACCESS_FLAGS_LIBPFF_ACCESS_FLAG_WRITE,
+    LIBPFF_RECOVERY_FLAGS_LIBPFF_RECOVERY_FLAG_IGNORE_ALLOCATION_DATA,
+    LIBPFF_RECOVERY_FLAGS_LIBPFF_RECOVERY_FLAG_SCAN_FOR_FRAGMENTS,
 };
 
+pub mod address;
+pub mod appointment;
 pub mod attachment;
+pub mod contact;
+pub mod distributionlist;
 mod encoding;
 pub mod error;
-mod filetime;
+mod file_io;
+pub mod filetime;
 pub mod folder;
+pub mod headers;
 pub mod item;
 pub mod message;
+mod mime;
 pub mod multivalue;
+pub mod namedproperty;
+pub mod path;
+pub mod propertyvalue;
 pub mod recipients;
 pub mod recordset;
+pub mod recurrence;
+pub mod rtf;
+pub mod task;
+pub mod threads;
+pub mod walk;
 
 #[derive(Debug)]
 pub struct Pff {
@@ -37,20 +65,77 @@ impl Pff {
         }
     }
 
-    pub fn open(mut self, path: &str, open_flags: FileOpenFlags) -> Result<PffOpen, error::Error> {
-        let mut error: *mut libpff_error_t = ptr::null_mut();
+    pub fn open(self, path: &str, open_flags: FileOpenFlags) -> Result<PffOpen, error::Error> {
         let path_str = CString::new(path)?;
+        self.open_c_str(&path_str, open_flags)
+    }
+
+    /// Opens the file at `path`. Unlike [`Pff::open`] this also works with
+    /// paths that are not valid UTF-8.
+    pub fn open_path<P: AsRef<Path>>(
+        self,
+        path: P,
+        open_flags: FileOpenFlags,
+    ) -> Result<PffOpen, error::Error> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+
+            let path_str = CString::new(path.as_ref().as_os_str().as_bytes())?;
+            self.open_c_str(&path_str, open_flags)
+        }
+
+        #[cfg(not(unix))]
+        {
+            let file = std::fs::File::open(path)?;
+            self.open_reader(file, open_flags)
+        }
+    }
+
+    /// Opens a PST/OST from any seekable stream, for e.g., an in-memory
+    /// buffer or an entry in an archive.
+    pub fn open_reader<R: Read + Seek + Send + 'static>(
+        mut self,
+        reader: R,
+        open_flags: FileOpenFlags,
+    ) -> Result<PffOpen, error::Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let file_io_handle = file_io::FileIoHandle::new(reader)?;
         let res = unsafe {
-            libpff_file_open(
+            libpff_file_open_file_io_handle(
                 self.file,
-                path_str.as_ptr(),
+                file_io_handle.as_ptr(),
                 open_flags.as_i32(),
                 &mut error,
             )
         };
         match res {
             1 => {
-                let pff_open = PffOpen { file: self.file };
+                let pff_open = PffOpen {
+                    file: self.file,
+                    file_io_handle: Some(file_io_handle),
+                };
+                self.file = ptr::null_mut();
+                Ok(pff_open)
+            }
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
+    fn open_c_str(
+        mut self,
+        path: &CStr,
+        open_flags: FileOpenFlags,
+    ) -> Result<PffOpen, error::Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let res =
+            unsafe { libpff_file_open(self.file, path.as_ptr(), open_flags.as_i32(), &mut error) };
+        match res {
+            1 => {
+                let pff_open = PffOpen {
+                    file: self.file,
+                    file_io_handle: None,
+                };
                 self.file = ptr::null_mut();
                 Ok(pff_open)
             }
@@ -68,6 +153,7 @@ impl Drop for Pff {
 #[derive(Debug)]
 pub struct PffOpen {
     file: *mut libpff_file_t,
+    file_io_handle: Option<file_io::FileIoHandle>,
 }
 
 impl PffOpen {
@@ -82,6 +168,40 @@ impl PffOpen {
         }
     }
 
+    pub fn content_type(&self) -> Result<ContentType, error::Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut content_type: u8 = 0;
+        let res = unsafe { libpff_file_get_content_type(self.file, &mut content_type, &mut error) };
+        match res {
+            1 => Ok(ContentType::try_from(content_type)
+                .map_err(|_| error::Error::BadContentType(content_type))?),
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
+    pub fn format(&self) -> Result<FileFormat, error::Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut file_type: u8 = 0;
+        let res = unsafe { libpff_file_get_type(self.file, &mut file_type, &mut error) };
+        match res {
+            1 => Ok(FileFormat::try_from(file_type)
+                .map_err(|_| error::Error::BadFileFormat(file_type))?),
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
+    pub fn encryption_type(&self) -> Result<EncryptionType, error::Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut encryption_type: u8 = 0;
+        let res =
+            unsafe { libpff_file_get_encryption_type(self.file, &mut encryption_type, &mut error) };
+        match res {
+            1 => Ok(EncryptionType::try_from(encryption_type)
+                .map_err(|_| error::Error::BadEncryptionType(encryption_type))?),
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
     pub fn root_item(&self) -> Result<Option<item::PffItem>, error::Error> {
         let mut error: *mut libpff_error_t = ptr::null_mut();
         let mut item: *mut libpff_item_t = ptr::null_mut();
@@ -103,12 +223,72 @@ impl PffOpen {
             _ => Err(error::Error::pff_error(error)),
         }
     }
+
+    /// Looks up an item by its identifier, for e.g. the one an entry ID in
+    /// the same file points at.
+    pub fn item_by_id(&self, id: u32) -> Result<Option<item::PffItem>, error::Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut item: *mut libpff_item_t = ptr::null_mut();
+        let res =
+            unsafe { libpff_file_get_item_by_identifier(self.file, id, &mut item, &mut error) };
+        match res {
+            1 => Ok(Some(item::PffItem::new(item))),
+            0 => Ok(None),
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
+    /// Scans the file for deleted items. This must be called before the
+    /// recovered items can be enumerated via [`PffOpen::recovered_items`].
+    pub fn recover_items(&self, recovery_flags: RecoveryFlags) -> Result<(), error::Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let res =
+            unsafe { libpff_file_recover_items(self.file, recovery_flags.as_i32(), &mut error) };
+        match res {
+            1 => Ok(()),
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
+    pub fn orphan_items_count(&self) -> Result<i32, error::Error> {
+        let mut count: i32 = 0;
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let res =
+            unsafe { libpff_file_get_number_of_orphan_items(self.file, &mut count, &mut error) };
+        match res {
+            1 => Ok(count),
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
+    pub fn orphan_items(&self) -> Result<OrphanItemsIterator<'_>, error::Error> {
+        OrphanItemsIterator::new(self)
+    }
+
+    pub fn recovered_items_count(&self) -> Result<i32, error::Error> {
+        let mut count: i32 = 0;
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let res =
+            unsafe { libpff_file_get_number_of_recovered_items(self.file, &mut count, &mut error) };
+        match res {
+            1 => Ok(count),
+            _ => Err(error::Error::pff_error(error)),
+        }
+    }
+
+    pub fn recovered_items(&self) -> Result<RecoveredItemsIterator<'_>, error::Error> {
+        RecoveredItemsIterator::new(self)
+    }
 }
 
 impl Drop for PffOpen {
     fn drop(&mut self) {
         unsafe { libpff_file_close(self.file, ptr::null_mut()) };
         unsafe { libpff_file_free(&mut self.file, ptr::null_mut()) };
+
+        // libpff reads through the IO handle till the file is closed so it
+        // can only be released now
+        self.file_io_handle.take();
     }
 }
 
@@ -126,9 +306,181 @@ impl FileOpenFlags {
     }
 }
 
+/// The kind of store held in the file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum ContentType {
+    /// Personal Address Book
+    Pab = b'a',
+    /// Personal Storage Table
+    Pst = b'p',
+    /// Offline Storage Table
+    Ost = b'o',
+}
+
+impl Display for ContentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ContentType::Pab => write!(f, "PAB"),
+            ContentType::Pst => write!(f, "PST"),
+            ContentType::Ost => write!(f, "OST"),
+        }
+    }
+}
+
+/// The on-disk format of the file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum FileFormat {
+    /// 32-bit ANSI file as written by Outlook 97 to 2002
+    Ansi32Bit = 32,
+    /// 64-bit Unicode file as written by Outlook 2003 and later
+    Unicode64Bit = 64,
+    /// 64-bit Unicode file with 4k pages as written by Outlook 2013 and later
+    /// for OSTs
+    Unicode64Bit4kPage = 65,
+}
+
+impl Display for FileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FileFormat::Ansi32Bit => write!(f, "32-bit ANSI"),
+            FileFormat::Unicode64Bit => write!(f, "64-bit Unicode"),
+            FileFormat::Unicode64Bit4kPage => write!(f, "64-bit Unicode (4k pages)"),
+        }
+    }
+}
+
+/// The encoding applied to the data blocks of the file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum EncryptionType {
+    None = 0,
+    Compressible = 1,
+    High = 2,
+}
+
+impl Display for EncryptionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EncryptionType::None => write!(f, "none"),
+            EncryptionType::Compressible => write!(f, "compressible"),
+            EncryptionType::High => write!(f, "high"),
+        }
+    }
+}
+
+bitflags! {
+    pub struct RecoveryFlags: u32 {
+        const IGNORE_ALLOCATION_DATA =
+            LIBPFF_RECOVERY_FLAGS_LIBPFF_RECOVERY_FLAG_IGNORE_ALLOCATION_DATA;
+        const SCAN_FOR_FRAGMENTS = LIBPFF_RECOVERY_FLAGS_LIBPFF_RECOVERY_FLAG_SCAN_FOR_FRAGMENTS;
+    }
+}
+
+impl RecoveryFlags {
+    pub fn as_i32(&self) -> i32 {
+        self.bits() as i32
+    }
+}
+
+pub struct OrphanItemsIterator<'a> {
+    file: &'a PffOpen,
+    count: i32,
+    index: i32,
+}
+
+impl<'a> OrphanItemsIterator<'a> {
+    pub fn new(file: &'a PffOpen) -> Result<Self, error::Error> {
+        Ok(OrphanItemsIterator {
+            file,
+            count: file.orphan_items_count()?,
+            index: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for OrphanItemsIterator<'a> {
+    type Item = Result<item::PffItem, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            None
+        } else {
+            let mut error: *mut libpff_error_t = ptr::null_mut();
+            let mut item: *mut libpff_item_t = ptr::null_mut();
+            let res = unsafe {
+                libpff_file_get_orphan_item_by_index(
+                    self.file.file,
+                    self.index,
+                    &mut item,
+                    &mut error,
+                )
+            };
+
+            // always move past the current index since a single broken
+            // item should not stall the enumeration
+            self.index += 1;
+
+            match res {
+                1 => Some(Ok(item::PffItem::new(item))),
+                _ => Some(Err(error::Error::pff_error(error))),
+            }
+        }
+    }
+}
+
+pub struct RecoveredItemsIterator<'a> {
+    file: &'a PffOpen,
+    count: i32,
+    index: i32,
+}
+
+impl<'a> RecoveredItemsIterator<'a> {
+    pub fn new(file: &'a PffOpen) -> Result<Self, error::Error> {
+        Ok(RecoveredItemsIterator {
+            file,
+            count: file.recovered_items_count()?,
+            index: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for RecoveredItemsIterator<'a> {
+    type Item = Result<item::PffItem, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            None
+        } else {
+            let mut error: *mut libpff_error_t = ptr::null_mut();
+            let mut item: *mut libpff_item_t = ptr::null_mut();
+            let res = unsafe {
+                libpff_file_get_recovered_item_by_index(
+                    self.file.file,
+                    self.index,
+                    &mut item,
+                    &mut error,
+                )
+            };
+
+            // always move past the current index since a single broken
+            // item should not stall the enumeration
+            self.index += 1;
+
+            match res {
+                1 => Some(Ok(item::PffItem::new(item))),
+                _ => Some(Err(error::Error::pff_error(error))),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{item::ItemExt, FileOpenFlags, Pff};
+    use std::{fs, io::Cursor, path::Path};
+
+    use crate::{item::ItemExt, ContentType, FileOpenFlags, Pff, RecoveryFlags};
 
     const TEST_PST_FILE: &str = "../data/sample.ost";
 
@@ -143,6 +495,24 @@ mod tests {
         let _ = pff.open(TEST_PST_FILE, FileOpenFlags::READ).unwrap();
     }
 
+    #[test]
+    fn file_open_path() {
+        let pff = Pff::new().unwrap();
+        let _ = pff
+            .open_path(Path::new(TEST_PST_FILE), FileOpenFlags::READ)
+            .unwrap();
+    }
+
+    #[test]
+    fn file_open_reader() {
+        let pff = Pff::new().unwrap();
+        let data = fs::read(TEST_PST_FILE).unwrap();
+        let pff = pff
+            .open_reader(Cursor::new(data), FileOpenFlags::READ)
+            .unwrap();
+        assert!(pff.root_folder().unwrap().is_some());
+    }
+
     #[test]
     fn non_existent_file() {
         let pff = Pff::new().unwrap();
@@ -159,6 +529,15 @@ mod tests {
         assert_ne!(size, 0);
     }
 
+    #[test]
+    fn file_metadata() {
+        let pff = Pff::new().unwrap();
+        let pff = pff.open(TEST_PST_FILE, FileOpenFlags::READ).unwrap();
+        assert_eq!(pff.content_type().unwrap(), ContentType::Ost);
+        assert!(pff.format().is_ok());
+        assert!(pff.encryption_type().is_ok());
+    }
+
     #[test]
     fn root_item() {
         let pff = Pff::new().unwrap();
@@ -173,4 +552,22 @@ mod tests {
         let folder = pff.root_folder().unwrap().unwrap();
         assert!(folder.id().is_ok());
     }
+
+    #[test]
+    fn orphan_items() {
+        let pff = Pff::new().unwrap();
+        let pff = pff.open(TEST_PST_FILE, FileOpenFlags::READ).unwrap();
+        let count = pff.orphan_items_count().unwrap();
+        assert_eq!(pff.orphan_items().unwrap().count(), count as usize);
+    }
+
+    #[test]
+    fn recovered_items() {
+        let pff = Pff::new().unwrap();
+        let pff = pff.open(TEST_PST_FILE, FileOpenFlags::READ).unwrap();
+        pff.recover_items(RecoveryFlags::empty()).unwrap();
+        for item in pff.recovered_items().unwrap() {
+            assert!(item.unwrap().id().is_ok());
+        }
+    }
 }
@@ -1,7 +1,9 @@
 use std::{fmt::Display, ptr};
 
+use bitflags::bitflags;
 use chrono::NaiveDateTime;
 use concat_idents::concat_idents;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use pff_sys::{
     libpff_error_t, libpff_item_free, libpff_item_t, libpff_message_get_attachment,
     libpff_message_get_client_submit_time, libpff_message_get_creation_time,
@@ -10,16 +12,20 @@ use pff_sys::{
     libpff_message_get_html_body_size, libpff_message_get_modification_time,
     libpff_message_get_number_of_attachments, libpff_message_get_plain_text_body,
     libpff_message_get_plain_text_body_size, libpff_message_get_recipients,
-    libpff_message_get_rtf_body, libpff_message_get_rtf_body_size,
 };
 
 use crate::{
+    address::AddressEntries,
     attachment::Attachment,
     encoding,
     error::Error,
     filetime::FileTime,
-    item::{EntryType, Item},
+    headers::Headers,
+    item::{EntryType, Item, ItemExt},
+    mime,
     recipients::Recipients,
+    rtf::{self, EncapsulatedBody},
+    threads::ConversationIndex,
 };
 
 #[derive(Debug)]
@@ -70,6 +76,26 @@ macro_rules! prop_string {
     };
 }
 
+/// Generates a method that returns the SMTP address of one of the
+/// addresses on the message, resolving Exchange addresses where possible.
+macro_rules! prop_address {
+    ($method:ident, $address_type:ident, $email_address:ident, $smtp_address:ident, $entry_id:ident) => {
+        pub fn $method(&self) -> Result<Option<String>, Error> {
+            let entries = AddressEntries {
+                address_type: self.get_entry_string_value(EntryType::$address_type)?,
+                email_address: self.get_entry_string_value(EntryType::$email_address)?,
+                smtp_address: self.get_entry_string_value(EntryType::$smtp_address)?,
+                entry_id: self
+                    .first_entry_by_type(EntryType::$entry_id)?
+                    .map(|entry| entry.as_buffer())
+                    .transpose()?,
+            };
+
+            Ok(entries.resolve())
+        }
+    };
+}
+
 macro_rules! prop_time {
     ($method:ident) => {
         concat_idents!(fn_name = libpff_message_get_, $method {
@@ -142,20 +168,86 @@ impl Display for MessageBodyType {
     }
 }
 
+bitflags! {
+    /// Message state flags stored in `PidTagMessageFlags`.
+    pub struct MessageFlags: u32 {
+        const READ = 0x0001;
+        const UNMODIFIED = 0x0002;
+        const SUBMITTED = 0x0004;
+        const UNSENT = 0x0008;
+        const HAS_ATTACHMENTS = 0x0010;
+        const FROM_ME = 0x0020;
+        const ASSOCIATED = 0x0040;
+        const RESEND = 0x0080;
+        const NOTIFY_READ = 0x0100;
+        const NOTIFY_UNREAD = 0x0200;
+        const EVER_READ = 0x0400;
+        const INTERNET = 0x2000;
+        const UNTRUSTED = 0x8000;
+    }
+}
+
+bitflags! {
+    /// Message status flags stored in `PidTagMessageStatus`.
+    pub struct MessageStatus: u32 {
+        const HIGHLIGHTED = 0x0001;
+        const TAGGED = 0x0002;
+        const HIDDEN = 0x0004;
+        const DELETE_MARKED = 0x0008;
+        const DRAFT = 0x0100;
+        const ANSWERED = 0x0200;
+        const IN_CONFLICT = 0x0800;
+        const REMOTE_DOWNLOAD = 0x1000;
+        const REMOTE_DELETE = 0x2000;
+    }
+}
+
+/// The follow up flag set on a message in `PidTagFlagStatus`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum FlagStatus {
+    Unflagged = 0,
+    Complete = 1,
+    Flagged = 2,
+}
+
 impl Message {
     prop_string!(message_class, MessageClass);
     prop_string!(subject, MessageSubject);
     prop_string!(conversation_topic, MessageConversationTopic);
     prop_string!(sender_name, MessageSenderName);
-    prop_string!(sender_email_address, MessageSenderEmailAddress);
+    prop_address!(
+        sender_email_address,
+        MessageSenderAddressType,
+        MessageSenderEmailAddress,
+        MessageSenderSmtpAddress,
+        MessageSenderEntryIdentifier
+    );
     prop_string!(sent_representing_name, MessageSentRepresentingName);
-    prop_string!(
+    prop_address!(
         sent_representing_email_address,
-        MessageSentRepresentingEmailAddress
+        MessageSentRepresentingAddressType,
+        MessageSentRepresentingEmailAddress,
+        MessageSentRepresentingSmtpAddress,
+        MessageSentRepresentingEntryIdentifier
     );
     prop_string!(received_by_name, MessageReceivedByName);
-    prop_string!(received_by_email_address, MessageReceivedByEmailAddress);
+    prop_address!(
+        received_by_email_address,
+        MessageReceivedByAddressType,
+        MessageReceivedByEmailAddress,
+        MessageReceivedBySmtpAddress,
+        MessageReceivedByEntryIdentifier
+    );
     prop_string!(transport_headers, MessageTransportHeaders);
+    prop_string!(internet_message_id, MessageInternetMessageId);
+
+    /// The parsed transport headers, if the message was received over SMTP.
+    pub fn headers(&self) -> Result<Option<Headers>, Error> {
+        Ok(self
+            .transport_headers()?
+            .map(|headers| Headers::parse(&headers)))
+    }
 
     prop_time!(client_submit_time);
     prop_time!(delivery_time);
@@ -167,31 +259,77 @@ impl Message {
         libpff_message_get_plain_text_body_size,
         libpff_message_get_plain_text_body
     );
-    prop_body!(
-        rtf_body,
-        libpff_message_get_rtf_body_size,
-        libpff_message_get_rtf_body
-    );
     prop_body!(
         html_body,
         libpff_message_get_html_body_size,
         libpff_message_get_html_body
     );
 
+    pub fn conversation_index(&self) -> Result<Option<ConversationIndex>, Error> {
+        Ok(self
+            .first_entry_by_type(EntryType::MessageConversationIndex)?
+            .map(|entry| entry.as_buffer())
+            .transpose()?
+            .and_then(|data| ConversationIndex::parse(&data)))
+    }
+
+    /// The LZFu compressed RTF body as stored in the file.
+    pub fn compressed_rtf_body(&self) -> Result<Option<Vec<u8>>, Error> {
+        self.first_entry_by_type(EntryType::MessageBodyCompressedRtf)?
+            .map(|entry| entry.as_buffer())
+            .transpose()
+    }
+
+    /// The decompressed RTF body.
+    pub fn rtf(&self) -> Result<Option<Vec<u8>>, Error> {
+        self.compressed_rtf_body()?
+            .map(|data| rtf::decompress(&data))
+            .transpose()
+    }
+
+    pub fn rtf_body(&self) -> Result<Option<String>, Error> {
+        // RTF is 7-bit ASCII with everything else escaped
+        Ok(self
+            .rtf()?
+            .map(|rtf| encoding_rs::mem::decode_latin1(&rtf).into_owned()))
+    }
+
     pub fn body(&self) -> Result<Option<(MessageBodyType, String)>, Error> {
         // try getting the body in this order: html, plain text, rtf
         match self.html_body()? {
             Some(body) => Ok(Some((MessageBodyType::Html, body))),
             None => match self.plain_text_body()? {
                 Some(body) => Ok(Some((MessageBodyType::PlainText, body))),
-                None => match self.rtf_body()? {
-                    Some(body) => Ok(Some((MessageBodyType::Rtf, body))),
-                    None => Ok(None),
-                },
+                None => self.rtf()?.map(rtf_to_body).transpose(),
             },
         }
     }
 
+    /// Rebuilds the message as an RFC 5322 (.eml) message including all its
+    /// bodies and attachments.
+    pub fn to_mime(&self) -> Result<Vec<u8>, Error> {
+        mime::to_mime(self)
+    }
+
+    pub fn flags(&self) -> Result<MessageFlags, Error> {
+        Ok(MessageFlags::from_bits_retain(
+            self.get_u32(EntryType::MessageFlags)?.unwrap_or_default(),
+        ))
+    }
+
+    pub fn status(&self) -> Result<MessageStatus, Error> {
+        Ok(MessageStatus::from_bits_retain(
+            self.get_u32(EntryType::MessageStatus)?.unwrap_or_default(),
+        ))
+    }
+
+    pub fn flag_status(&self) -> Result<FlagStatus, Error> {
+        let flag_status = self
+            .get_u32(EntryType::MessageFlagStatus)?
+            .unwrap_or_default();
+        FlagStatus::try_from(flag_status).map_err(|_| Error::BadFlagStatus(flag_status))
+    }
+
     pub fn sender(&self) -> Result<Option<String>, Error> {
         let sender_name = self.sender_name()?;
         let sender_email = self.sender_email_address()?;
@@ -238,6 +376,13 @@ impl Message {
         AttachmentsIterator::new(self)
     }
 
+    fn get_entry_string_value(&self, entry_type: EntryType) -> Result<Option<String>, Error> {
+        match self.get_entry_string_size(entry_type)? {
+            Some(entry_size) if entry_size > 0 => self.get_entry_string(entry_type, entry_size),
+            _ => Ok(None),
+        }
+    }
+
     fn get_entry_string_size(&self, entry_type: EntryType) -> Result<Option<usize>, Error> {
         let mut error: *mut libpff_error_t = ptr::null_mut();
         let mut entry_size = 0;
@@ -337,6 +482,19 @@ impl<'a> Iterator for AttachmentsIterator<'a> {
     }
 }
 
+/// Recovers the original HTML or plain text body if the RTF body is just a
+/// wrapper around it.
+fn rtf_to_body(rtf: Vec<u8>) -> Result<(MessageBodyType, String), Error> {
+    Ok(match rtf::de_encapsulate(&rtf) {
+        Some(EncapsulatedBody::Html(html)) => (MessageBodyType::Html, html),
+        Some(EncapsulatedBody::Text(text)) => (MessageBodyType::PlainText, text),
+        None => (
+            MessageBodyType::Rtf,
+            encoding_rs::mem::decode_latin1(&rtf).into_owned(),
+        ),
+    })
+}
+
 fn attachments_count(message: &Message) -> Result<i32, Error> {
     // get number of attachments
     let mut count: i32 = 0;
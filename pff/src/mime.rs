@@ -0,0 +1,534 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::NaiveDateTime;
+
+use crate::{
+    error::Error,
+    item::ItemExt,
+    message::{Message, MessageBodyType},
+    recipients::{Recipient, RecipientKind},
+};
+
+/// Maximum length of a line of base64 encoded data
+const LINE_LEN: usize = 76;
+
+/// Maximum number of bytes of input encoded into a single RFC 2047 encoded
+/// word; this keeps encoded words well under the 75 character limit.
+const ENCODED_WORD_LEN: usize = 45;
+
+/// Headers from the original transport headers that describe the MIME
+/// structure of the original message. Since we rebuild the structure these
+/// are replaced by our own.
+const STRUCTURE_HEADERS: [&str; 4] = [
+    "mime-version",
+    "content-type",
+    "content-transfer-encoding",
+    "content-disposition",
+];
+
+pub(crate) struct Part {
+    headers: Vec<(String, String)>,
+    body: PartBody,
+}
+
+enum PartBody {
+    /// Transfer encoded data
+    Data(Vec<u8>),
+    Multipart(&'static str, Vec<Part>),
+}
+
+impl Part {
+    pub(crate) fn text(subtype: &str, text: &str) -> Self {
+        Part {
+            headers: vec![
+                (
+                    "Content-Type".to_string(),
+                    format!("text/{subtype}; charset=utf-8"),
+                ),
+                (
+                    "Content-Transfer-Encoding".to_string(),
+                    "base64".to_string(),
+                ),
+            ],
+            body: PartBody::Data(base64_lines(text.as_bytes())),
+        }
+    }
+
+    pub(crate) fn attachment(content_type: &str, file_name: &str, data: &[u8]) -> Self {
+        Part::file("attachment", content_type, file_name, data)
+    }
+
+    /// An attachment displayed as part of the HTML body, which references it
+    /// with a `cid:` URL.
+    pub(crate) fn inline(
+        content_type: &str,
+        file_name: &str,
+        content_id: &str,
+        data: &[u8],
+    ) -> Self {
+        let mut part = Part::file("inline", content_type, file_name, data);
+        part.headers
+            .push(("Content-ID".to_string(), format!("<{content_id}>")));
+        part
+    }
+
+    fn file(disposition: &str, content_type: &str, file_name: &str, data: &[u8]) -> Self {
+        Part {
+            headers: vec![
+                (
+                    "Content-Type".to_string(),
+                    format!("{content_type}; {}", param("name", file_name)),
+                ),
+                (
+                    "Content-Transfer-Encoding".to_string(),
+                    "base64".to_string(),
+                ),
+                (
+                    "Content-Disposition".to_string(),
+                    format!("{disposition}; {}", param("filename", file_name)),
+                ),
+            ],
+            body: PartBody::Data(base64_lines(data)),
+        }
+    }
+
+    /// An attached message. These are not transfer encoded so that mail
+    /// clients can show them inline.
+    pub(crate) fn message(file_name: &str, message: Vec<u8>) -> Self {
+        Part {
+            headers: vec![
+                ("Content-Type".to_string(), "message/rfc822".to_string()),
+                (
+                    "Content-Disposition".to_string(),
+                    format!("attachment; {}", param("filename", file_name)),
+                ),
+            ],
+            body: PartBody::Data(message),
+        }
+    }
+
+    pub(crate) fn multipart(subtype: &'static str, parts: Vec<Part>) -> Self {
+        Part {
+            headers: vec![],
+            body: PartBody::Multipart(subtype, parts),
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>, boundary_prefix: &str, boundary_index: &mut usize) {
+        for (name, value) in &self.headers {
+            write_header(out, name, value);
+        }
+
+        match &self.body {
+            PartBody::Data(data) => {
+                out.extend_from_slice(b"\r\n");
+                out.extend_from_slice(data);
+            }
+            PartBody::Multipart(subtype, parts) => {
+                let boundary = format!("{boundary_prefix}_{boundary_index}");
+                *boundary_index += 1;
+
+                write_header(
+                    out,
+                    "Content-Type",
+                    &format!("multipart/{subtype}; boundary=\"{boundary}\""),
+                );
+                out.extend_from_slice(b"\r\n");
+                for part in parts {
+                    out.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+                    part.write(out, boundary_prefix, boundary_index);
+                    out.extend_from_slice(b"\r\n");
+                }
+                out.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+            }
+        }
+    }
+}
+
+/// Rebuilds an RFC 5322 message from `message`. The original transport
+/// headers are used when the message has them, otherwise the headers are
+/// synthesized from the message properties.
+pub(crate) fn to_mime(message: &Message) -> Result<Vec<u8>, Error> {
+    write_message(message, 0)
+}
+
+/// `depth` is the number of messages `message` is embedded in. It is part of
+/// the boundaries so that those of embedded messages never clash with the
+/// ones of the message they are attached to.
+fn write_message(message: &Message, depth: usize) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+
+    match message.transport_headers()? {
+        Some(headers) if !headers.trim().is_empty() => {
+            write_transport_headers(&mut out, &headers);
+        }
+        _ => write_synthesized_headers(&mut out, message)?,
+    }
+    write_header(&mut out, "MIME-Version", "1.0");
+
+    let (inline, attachments) = attachment_parts(message, depth)?;
+
+    // images referenced from the HTML body go along with the body in a
+    // multipart/related part and everything else is attached
+    let mut root = body_part(message)?;
+    if !inline.is_empty() {
+        root = Part::multipart("related", std::iter::once(root).chain(inline).collect());
+    }
+    if !attachments.is_empty() {
+        root = Part::multipart("mixed", std::iter::once(root).chain(attachments).collect());
+    }
+
+    let boundary_prefix = format!("----=_pff_{:08x}_{depth}", message.id()?);
+    root.write(&mut out, &boundary_prefix, &mut 0);
+
+    Ok(out)
+}
+
+fn body_part(message: &Message) -> Result<Part, Error> {
+    let plain = message.plain_text_body()?;
+    let html = message.html_body()?;
+
+    Ok(match (plain, html) {
+        (Some(plain), Some(html)) => Part::multipart(
+            "alternative",
+            vec![Part::text("plain", &plain), Part::text("html", &html)],
+        ),
+        (None, Some(html)) => Part::text("html", &html),
+        (Some(plain), None) => Part::text("plain", &plain),
+        (None, None) => match message.body()? {
+            // messages with only an RTF body
+            Some((MessageBodyType::Html, html)) => Part::text("html", &html),
+            Some((MessageBodyType::PlainText, text)) => Part::text("plain", &text),
+            Some((MessageBodyType::Rtf, rtf)) => Part::text("rtf", &rtf),
+            None => Part::text("plain", ""),
+        },
+    })
+}
+
+/// Returns the inline attachments and the regular attachments of the message.
+fn attachment_parts(message: &Message, depth: usize) -> Result<(Vec<Part>, Vec<Part>), Error> {
+    let mut inline = vec![];
+    let mut attachments = vec![];
+
+    for (index, attachment) in message.attachments()?.enumerate() {
+        let attachment = attachment?;
+        let name = attachment
+            .file_name()?
+            .unwrap_or_else(|| format!("attachment_{}", index + 1));
+
+        if let Some(embedded) = attachment.embedded_message()? {
+            attachments.push(Part::message(
+                &format!("{name}.eml"),
+                write_message(&embedded, depth + 1)?,
+            ));
+            continue;
+        }
+
+        let content_type = attachment
+            .mime_tag()?
+            .filter(|mime_tag| is_content_type(mime_tag))
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        let data = attachment.as_buffer()?;
+
+        let content_id = attachment
+            .content_id()?
+            .map(|content_id| content_id.trim_matches(['<', '>', ' ']).to_string())
+            .filter(|content_id| is_id(content_id));
+        match content_id {
+            Some(content_id) if attachment.is_inline()? => {
+                inline.push(Part::inline(&content_type, &name, &content_id, &data))
+            }
+            _ => attachments.push(Part::attachment(&content_type, &name, &data)),
+        }
+    }
+
+    Ok((inline, attachments))
+}
+
+/// Checks that a MIME tag read from the file looks like `type/subtype` so it
+/// can't break the header it is written to.
+fn is_content_type(value: &str) -> bool {
+    let mut parts = value.split('/');
+    let valid = |part: Option<&str>| {
+        part.is_some_and(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+        })
+    };
+
+    valid(parts.next()) && valid(parts.next()) && parts.next().is_none()
+}
+
+/// Checks that an ID read from the file, for e.g. a message ID, is printable
+/// ASCII without any whitespace so it can't break the header it is written
+/// to.
+fn is_id(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_graphic())
+}
+
+fn write_transport_headers(out: &mut Vec<u8>, headers: &str) {
+    let mut skip = false;
+
+    for line in headers.lines() {
+        // the header block ends at the first empty line
+        if line.trim().is_empty() {
+            break;
+        }
+
+        // lines starting with whitespace continue the previous header
+        if !line.starts_with([' ', '\t']) {
+            let name = line.split(':').next().unwrap_or_default().trim();
+            skip = STRUCTURE_HEADERS
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case(name));
+        }
+
+        if !skip {
+            out.extend_from_slice(line.as_bytes());
+            out.extend_from_slice(b"\r\n");
+        }
+    }
+}
+
+fn write_synthesized_headers(out: &mut Vec<u8>, message: &Message) -> Result<(), Error> {
+    if let Some(date) = message.client_submit_time()?.or(message.delivery_time()?) {
+        write_header(out, "Date", &format_date(date));
+    }
+
+    if let Some(from) = format_address(
+        message.sender_name()?.as_deref(),
+        message.sender_email_address()?.as_deref(),
+    ) {
+        write_header(out, "From", &from);
+    }
+
+    let recipients = message
+        .recipients()?
+        .map(|recipients| recipients.list())
+        .transpose()?
+        .unwrap_or_default();
+    for (name, kinds) in [
+        ("To", &[Some(RecipientKind::To), None][..]),
+        ("Cc", &[Some(RecipientKind::Cc)]),
+        ("Bcc", &[Some(RecipientKind::Bcc)]),
+    ] {
+        let recipients = recipients
+            .iter()
+            .filter(|r| kinds.contains(&r.kind))
+            .collect::<Vec<_>>();
+        if let Some(addresses) = format_address_list(&recipients) {
+            write_header(out, name, &addresses);
+        }
+    }
+
+    if let Some(subject) = message.subject()? {
+        write_header(out, "Subject", &encode_header_value(&subject));
+    }
+
+    // a message ID that isn't safe to write is left out rather than
+    // sanitized since it wouldn't match the original anyway
+    let message_id = message.internet_message_id()?;
+    if let Some(message_id) = message_id.as_deref().map(str::trim).filter(|id| is_id(id)) {
+        write_header(out, "Message-ID", message_id);
+    }
+
+    Ok(())
+}
+
+fn write_header(out: &mut Vec<u8>, name: &str, value: &str) {
+    out.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
+}
+
+pub(crate) fn format_date(date: NaiveDateTime) -> String {
+    date.and_utc().to_rfc2822()
+}
+
+pub(crate) fn format_address(name: Option<&str>, email: Option<&str>) -> Option<String> {
+    let name = name.map(str::trim).filter(|name| !name.is_empty());
+    let email = email.map(str::trim).filter(|email| !email.is_empty());
+
+    match (name, email) {
+        (Some(name), Some(email)) if name != email => {
+            Some(format!("{} <{email}>", encode_display_name(name)))
+        }
+        (_, Some(email)) => Some(email.to_string()),
+        // a bare name would be read as an address
+        (_, None) => None,
+    }
+}
+
+/// Formats an empty group, which is how a recipient that only has a name
+/// can be kept in an address list.
+fn format_group(name: Option<&str>) -> Option<String> {
+    name.map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| format!("{}:;", encode_display_name(name)))
+}
+
+fn format_address_list(recipients: &[&Recipient]) -> Option<String> {
+    let addresses = recipients
+        .iter()
+        .filter_map(|r| {
+            let name = r.display_name.as_deref();
+            format_address(name, r.email_address.as_deref()).or_else(|| format_group(name))
+        })
+        .collect::<Vec<_>>();
+
+    match addresses.is_empty() {
+        true => None,
+        false => Some(addresses.join(",\r\n ")),
+    }
+}
+
+fn encode_display_name(name: &str) -> String {
+    if !is_plain_ascii(name) {
+        encode_words(name)
+    } else if name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || " !#$%&'*+-/=?^_`{|}~".contains(c))
+    {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Encodes `value` using RFC 2047 encoded words if it isn't plain ASCII.
+pub(crate) fn encode_header_value(value: &str) -> String {
+    match is_plain_ascii(value) {
+        true => value.to_string(),
+        false => encode_words(value),
+    }
+}
+
+fn is_plain_ascii(value: &str) -> bool {
+    value.chars().all(|c| c.is_ascii() && !c.is_ascii_control())
+}
+
+fn encode_words(value: &str) -> String {
+    let mut words = vec![];
+    let mut start = 0;
+
+    // split on character boundaries so that each encoded word holds valid
+    // UTF-8 on its own
+    for (index, c) in value.char_indices() {
+        if index + c.len_utf8() - start > ENCODED_WORD_LEN {
+            words.push(&value[start..index]);
+            start = index;
+        }
+    }
+    words.push(&value[start..]);
+
+    words
+        .into_iter()
+        .map(|word| format!("=?utf-8?B?{}?=", STANDARD.encode(word)))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+/// Formats a header parameter, using RFC 2231 encoding for values that
+/// aren't plain ASCII.
+fn param(name: &str, value: &str) -> String {
+    if is_plain_ascii(value) {
+        format!(
+            "{name}=\"{}\"",
+            value.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    } else {
+        let encoded = value
+            .bytes()
+            .map(
+                |b| match b.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&b) {
+                    true => (b as char).to_string(),
+                    false => format!("%{b:02X}"),
+                },
+            )
+            .collect::<String>();
+        format!("{name}*=utf-8''{encoded}")
+    }
+}
+
+fn base64_lines(data: &[u8]) -> Vec<u8> {
+    let encoded = STANDARD.encode(data);
+    let mut out = Vec::with_capacity(encoded.len() + encoded.len() / LINE_LEN * 2 + 2);
+
+    for line in encoded.as_bytes().chunks(LINE_LEN) {
+        out.extend_from_slice(line);
+        out.extend_from_slice(b"\r\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mime::{
+        encode_header_value, format_address, format_group, is_content_type, is_id, param,
+        write_transport_headers,
+    };
+
+    #[test]
+    fn header_encoding() {
+        assert_eq!(encode_header_value("Hello"), "Hello");
+        assert_eq!(encode_header_value("Grüße"), "=?utf-8?B?R3LDvMOfZQ==?=");
+    }
+
+    #[test]
+    fn addresses() {
+        assert_eq!(
+            format_address(Some("Alice"), Some("alice@email.com")).unwrap(),
+            "Alice <alice@email.com>"
+        );
+        assert_eq!(
+            format_address(Some("Beesly, Pam"), Some("pam@email.com")).unwrap(),
+            "\"Beesly, Pam\" <pam@email.com>"
+        );
+        assert_eq!(
+            format_address(None, Some("bob@email.com")).unwrap(),
+            "bob@email.com"
+        );
+        assert!(format_address(Some(" "), None).is_none());
+        assert!(format_address(Some("Alice"), None).is_none());
+        assert_eq!(format_group(Some("Alice")).unwrap(), "Alice:;");
+        assert_eq!(
+            format_group(Some("Beesly, Pam")).unwrap(),
+            "\"Beesly, Pam\":;"
+        );
+        assert!(format_group(Some(" ")).is_none());
+    }
+
+    #[test]
+    fn params() {
+        assert_eq!(param("filename", "a b.txt"), "filename=\"a b.txt\"");
+        assert_eq!(param("filename", "ü.txt"), "filename*=utf-8''%C3%BC.txt");
+    }
+
+    #[test]
+    fn content_types() {
+        assert!(is_content_type("image/png"));
+        assert!(is_content_type("application/vnd.ms-excel"));
+        assert!(!is_content_type("image"));
+        assert!(!is_content_type("text/plain; charset=utf-8"));
+        assert!(!is_content_type("a/b/c"));
+    }
+
+    #[test]
+    fn ids() {
+        assert!(is_id("image001.png@01D8F0A1.2B3C4D50"));
+        assert!(!is_id(""));
+        assert!(!is_id("a@b\r\nBcc: eve@email.com"));
+        assert!(!is_id("a b@c"));
+    }
+
+    #[test]
+    fn transport_headers_drop_structure() {
+        let headers = "Subject: Hi\r\nContent-Type: multipart/mixed;\r\n\tboundary=\"x\"\r\nTo: a@b.c\r\n\r\n";
+        let mut out = vec![];
+        write_transport_headers(&mut out, headers);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Subject: Hi\r\nTo: a@b.c\r\n"
+        );
+    }
+}
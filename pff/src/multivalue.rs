@@ -1,4 +1,4 @@
-use std::{ffi::CString, ptr};
+use std::{ffi::CString, ptr, slice};
 
 use chrono::NaiveDateTime;
 use concat_idents::concat_idents;
@@ -12,7 +12,7 @@ use pff_sys::{
 };
 use uuid::Uuid;
 
-use crate::{error::Error, filetime::FileTime, item::ValueType};
+use crate::{error::Error, filetime::FileTime, item::ValueType, propertyvalue::PropertyValue};
 
 #[derive(Debug)]
 pub struct MultiValue {
@@ -83,7 +83,9 @@ impl<'a> Iterator for MultiValueEntryIterator<'a> {
         if self.index >= self.count {
             None
         } else {
-            Some(MultiValueEntry::new(self.multi_value, self.index))
+            let entry = MultiValueEntry::new(self.multi_value, self.index);
+            self.index += 1;
+            Some(entry)
         }
     }
 }
@@ -92,7 +94,7 @@ pub struct MultiValueEntry<'a> {
     multi_value: &'a MultiValue,
     index: i32,
     value_type: ValueType,
-    value_size: usize,
+    value_data: &'a [u8],
 }
 
 macro_rules! data_get {
@@ -135,7 +137,12 @@ impl<'a> MultiValueEntry<'a> {
                 index,
                 value_type: ValueType::try_from(value_type)
                     .map_err(|_| Error::BadValueType(value_type))?,
-                value_size,
+                // the value data points into the multi value which we hold a
+                // reference to so it lives at least as long as we do
+                value_data: match value_data.is_null() {
+                    true => &[],
+                    false => unsafe { slice::from_raw_parts(value_data, value_size) },
+                },
             }),
             _ => Err(Error::pff_error(error)),
         }
@@ -146,7 +153,17 @@ impl<'a> MultiValueEntry<'a> {
     }
 
     pub fn value_size(&self) -> usize {
-        self.value_size
+        self.value_data.len()
+    }
+
+    /// Decodes this value. Values of multi-value types are returned as the
+    /// corresponding single value variant.
+    pub fn value(&self) -> Result<PropertyValue, Error> {
+        match self.value_type.element_type() {
+            ValueType::StringAscii => Ok(PropertyValue::StringAscii(self.as_string()?)),
+            ValueType::StringUnicode => Ok(PropertyValue::StringUnicode(self.as_string()?)),
+            value_type => PropertyValue::from_data(value_type, self.value_data),
+        }
     }
 
     fn string_size(&self) -> Result<usize, Error> {
@@ -237,6 +254,10 @@ impl<'a> MultiValueEntry<'a> {
         }
     }
 
+    /// Returns the GUID with its bytes read in the order they are stored,
+    /// i.e., big endian, which is what this has always done. MAPI stores
+    /// GUIDs little endian though, so use [`MultiValueEntry::value`] to get
+    /// the GUID that Outlook and the property set constants use.
     pub fn as_guid(&self) -> Result<Uuid, Error> {
         let mut buf: [u8; 16] = [0; 16];
         let buf_ptr = buf.as_mut_ptr();
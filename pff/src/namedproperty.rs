@@ -0,0 +1,205 @@
+use std::{ffi::CString, fmt::Display, ptr};
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use pff_sys::{
+    libpff_error_t, libpff_name_to_id_map_entry_free, libpff_name_to_id_map_entry_get_guid,
+    libpff_name_to_id_map_entry_get_number, libpff_name_to_id_map_entry_get_type,
+    libpff_name_to_id_map_entry_get_utf8_string, libpff_name_to_id_map_entry_get_utf8_string_size,
+    libpff_name_to_id_map_entry_t,
+};
+use uuid::{uuid, Uuid};
+
+use crate::error::Error;
+
+/// GUIDs of the well known property sets as listed in [MS-OXPROPS].
+///
+/// [MS-OXPROPS]: https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxprops
+pub mod property_set {
+    use super::{uuid, Uuid};
+
+    pub const PS_MAPI: Uuid = uuid!("00020328-0000-0000-c000-000000000046");
+    pub const PS_PUBLIC_STRINGS: Uuid = uuid!("00020329-0000-0000-c000-000000000046");
+    pub const PS_INTERNET_HEADERS: Uuid = uuid!("00020386-0000-0000-c000-000000000046");
+    pub const PSETID_APPOINTMENT: Uuid = uuid!("00062002-0000-0000-c000-000000000046");
+    pub const PSETID_TASK: Uuid = uuid!("00062003-0000-0000-c000-000000000046");
+    pub const PSETID_ADDRESS: Uuid = uuid!("00062004-0000-0000-c000-000000000046");
+    pub const PSETID_COMMON: Uuid = uuid!("00062008-0000-0000-c000-000000000046");
+    pub const PSETID_LOG: Uuid = uuid!("0006200a-0000-0000-c000-000000000046");
+    pub const PSETID_NOTE: Uuid = uuid!("0006200e-0000-0000-c000-000000000046");
+    pub const PSETID_SHARING: Uuid = uuid!("00062040-0000-0000-c000-000000000046");
+    pub const PSETID_POST_RSS: Uuid = uuid!("00062041-0000-0000-c000-000000000046");
+    pub const PSETID_MEETING: Uuid = uuid!("6ed8da90-450b-101b-98da-00aa003f1305");
+    pub const PSETID_MESSAGING: Uuid = uuid!("41f28f13-83f4-4114-a584-eedb5a6b0bff");
+    pub const PSETID_UNIFIED_MESSAGING: Uuid = uuid!("4442858e-a9e3-4e80-b900-317a210cc15b");
+    pub const PSETID_AIR_SYNC: Uuid = uuid!("71035549-0739-4dcb-9163-00f0580dbbdf");
+    pub const PSETID_ATTACHMENT: Uuid = uuid!("96357f7f-59e1-47d0-99a7-46515c183b54");
+}
+
+/// The name of a property within its property set. Properties are either
+/// identified by a number (the "LID") or by a string.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum PropertyName {
+    Id(u32),
+    String(String),
+}
+
+impl From<u32> for PropertyName {
+    fn from(id: u32) -> Self {
+        PropertyName::Id(id)
+    }
+}
+
+impl From<&str> for PropertyName {
+    fn from(name: &str) -> Self {
+        PropertyName::String(name.to_string())
+    }
+}
+
+impl From<String> for PropertyName {
+    fn from(name: String) -> Self {
+        PropertyName::String(name)
+    }
+}
+
+impl Display for PropertyName {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PropertyName::Id(id) => write!(f, "{id:#06x}"),
+            PropertyName::String(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// A property whose entry type in the file is only meaningful once resolved
+/// through the file's name-to-ID map.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct NamedProperty {
+    pub guid: Uuid,
+    pub name: PropertyName,
+}
+
+impl NamedProperty {
+    pub fn new<N: Into<PropertyName>>(guid: Uuid, name: N) -> Self {
+        Self {
+            guid,
+            name: name.into(),
+        }
+    }
+}
+
+impl Display for NamedProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{{{}}}:{}", self.guid, self.name)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+enum NameToIdMapEntryType {
+    Numeric = b'n',
+    String = b's',
+}
+
+pub(crate) struct NameToIdMapEntry {
+    entry: *mut libpff_name_to_id_map_entry_t,
+}
+
+impl Drop for NameToIdMapEntry {
+    fn drop(&mut self) {
+        unsafe { libpff_name_to_id_map_entry_free(&mut self.entry, ptr::null_mut()) };
+    }
+}
+
+impl NameToIdMapEntry {
+    pub(crate) fn new(entry: *mut libpff_name_to_id_map_entry_t) -> Self {
+        Self { entry }
+    }
+
+    pub(crate) fn to_named_property(&self) -> Result<NamedProperty, Error> {
+        let name = match self.type_()? {
+            NameToIdMapEntryType::Numeric => PropertyName::Id(self.number()?),
+            NameToIdMapEntryType::String => PropertyName::String(self.string()?),
+        };
+
+        Ok(NamedProperty {
+            guid: self.guid()?,
+            name,
+        })
+    }
+
+    fn type_(&self) -> Result<NameToIdMapEntryType, Error> {
+        let mut entry_type: u8 = 0;
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+
+        let res = unsafe {
+            libpff_name_to_id_map_entry_get_type(self.entry, &mut entry_type, &mut error)
+        };
+        match res {
+            1 => Ok(NameToIdMapEntryType::try_from(entry_type)
+                .map_err(|_| Error::BadNamedPropertyType(entry_type))?),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+
+    fn number(&self) -> Result<u32, Error> {
+        let mut number: u32 = 0;
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+
+        let res =
+            unsafe { libpff_name_to_id_map_entry_get_number(self.entry, &mut number, &mut error) };
+        match res {
+            1 => Ok(number),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+
+    fn string_size(&self) -> Result<usize, Error> {
+        let mut str_size = 0;
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+
+        let res = unsafe {
+            libpff_name_to_id_map_entry_get_utf8_string_size(self.entry, &mut str_size, &mut error)
+        };
+        match res {
+            1 => Ok(str_size),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+
+    fn string(&self) -> Result<String, Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let str_size = self.string_size()?;
+        let mut buf = Vec::<u8>::with_capacity(str_size);
+        let buf_ptr = buf.as_mut_ptr();
+
+        let res = unsafe {
+            let res = libpff_name_to_id_map_entry_get_utf8_string(
+                self.entry, buf_ptr, str_size, &mut error,
+            );
+            if res == 1 {
+                buf.set_len(str_size);
+            }
+            res
+        };
+
+        match res {
+            1 => Ok(CString::from_vec_with_nul(buf)?.into_string()?),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+
+    fn guid(&self) -> Result<Uuid, Error> {
+        let mut buf: [u8; 16] = [0; 16];
+        let buf_ptr = buf.as_mut_ptr();
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+
+        let res = unsafe {
+            libpff_name_to_id_map_entry_get_guid(self.entry, buf_ptr, buf.len(), &mut error)
+        };
+        match res {
+            // GUIDs are stored in the little endian layout used by Windows
+            1 => Ok(Uuid::from_bytes_le(buf)),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
+}
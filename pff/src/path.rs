@@ -0,0 +1,384 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    error::Error,
+    folder::Folder,
+    item::{ItemExt, ItemType, PffItem},
+    PffOpen,
+};
+
+/// The names of the folders from the root folder down to a folder, not
+/// including the root folder itself.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct FolderPath(Vec<String>);
+
+impl FolderPath {
+    pub fn new(names: Vec<String>) -> Self {
+        FolderPath(names)
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.0
+    }
+
+    /// The number of folders below the root folder, 0 for the root folder.
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The path of the sub folder `name` of this folder.
+    pub fn join(&self, name: impl Into<String>) -> Self {
+        let mut names = self.0.clone();
+        names.push(name.into());
+        FolderPath(names)
+    }
+}
+
+impl Display for FolderPath {
+    /// Formats the path like a file system path, for e.g. `/Inbox/Projects`,
+    /// or `/` for the root folder.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "/");
+        }
+        for name in &self.0 {
+            write!(f, "/{name}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The identifiers of the items from the root folder down to an item, not
+/// including the root folder itself. Its string form is the identifiers
+/// joined with `_`, for e.g. `8354_8514_8546_7029316`.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ItemIdPath(Vec<u32>);
+
+impl ItemIdPath {
+    pub fn new(ids: Vec<u32>) -> Self {
+        ItemIdPath(ids)
+    }
+
+    pub fn ids(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// The identifier of the item the path leads to, `None` for the root
+    /// folder.
+    pub fn id(&self) -> Option<u32> {
+        self.0.last().copied()
+    }
+
+    /// The path of the item `id` within the folder this path leads to.
+    pub fn join(&self, id: u32) -> Self {
+        let mut ids = self.0.clone();
+        ids.push(id);
+        ItemIdPath(ids)
+    }
+
+    /// Looks up the item the path leads to in `pff`.
+    pub fn resolve(&self, pff: &PffOpen) -> Result<PffItem, Error> {
+        let not_found = || Error::ItemNotFound(self.to_string());
+
+        let mut item = pff.root_folder()?.ok_or_else(not_found)?;
+        for id in &self.0 {
+            item = item.sub_item_by_id(*id)?.ok_or_else(not_found)?;
+        }
+
+        Ok(item)
+    }
+
+    /// Returns the path as folder names followed by the identifier of the
+    /// item if it's not a folder, for e.g. `/Inbox/Projects/7029316`.
+    /// A `/` in a folder name is written as `%2F` (and `%` as `%25`).
+    /// Folders whose name is shared with a sibling, is a number or is
+    /// missing are given as `Name (id)`, for e.g. `/Inbox (8514)`.
+    pub fn display_path(&self, pff: &PffOpen) -> Result<String, Error> {
+        let not_found = || Error::ItemNotFound(self.to_string());
+
+        let mut path = String::new();
+        let mut parent = pff.root_folder()?.ok_or_else(not_found)?;
+        for id in &self.0 {
+            let item = parent
+                .sub_item_by_id::<PffItem>(*id)?
+                .ok_or_else(not_found)?;
+            let segment = match (parent.type_()?, item.type_()?) {
+                (ItemType::Folder, ItemType::Folder) => {
+                    let sub_folders = sub_folder_names(&parent.into_folder()?)?;
+                    folder_segment(*id, &sub_folders)
+                }
+                _ => id.to_string(),
+            };
+            path.push('/');
+            path.push_str(&segment);
+            parent = item;
+        }
+
+        match path.is_empty() {
+            true => Ok("/".to_string()),
+            false => Ok(path),
+        }
+    }
+
+    /// Parses a path in the form returned by [`ItemIdPath::display_path`].
+    /// Folder names are matched ignoring case unless that makes them
+    /// ambiguous.
+    pub fn from_display_path(pff: &PffOpen, path: &str) -> Result<Self, Error> {
+        let not_found = || Error::ItemNotFound(path.to_string());
+
+        let mut folder = pff.root_folder()?.ok_or_else(not_found)?.into_folder()?;
+        let mut ids = vec![];
+        let mut segments = path.split('/').filter(|name| !name.is_empty()).peekable();
+        while let Some(segment) = segments.next() {
+            let sub_folders = sub_folder_names(&folder)?;
+            if let Some(id) = find_folder_segment(segment, &sub_folders) {
+                ids.push(id);
+                folder = folder.sub_item_by_id::<Folder>(id)?.ok_or_else(not_found)?;
+                continue;
+            }
+
+            // only the last part of the path can be the ID of an item
+            let id = match (segment.parse::<u32>(), segments.peek()) {
+                (Ok(id), None) => id,
+                _ => return Err(not_found()),
+            };
+            folder
+                .sub_item_by_id::<PffItem>(id)?
+                .ok_or_else(not_found)?;
+            ids.push(id);
+        }
+
+        Ok(ItemIdPath(ids))
+    }
+}
+
+/// The identifiers and names of the sub folders of `folder`, with an empty
+/// name for folders that don't have one.
+fn sub_folder_names(folder: &Folder) -> Result<Vec<(u32, String)>, Error> {
+    folder
+        .sub_folders()?
+        .map(|sub_folder| {
+            let sub_folder = sub_folder?;
+            Ok((sub_folder.id()?, sub_folder.name()?.unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// The part of a display path that stands for the folder `id` among its
+/// `siblings` (which include the folder itself).
+fn folder_segment(id: u32, siblings: &[(u32, String)]) -> String {
+    let name = siblings
+        .iter()
+        .find(|(sibling_id, _)| *sibling_id == id)
+        .map(|(_, name)| name.as_str())
+        .unwrap_or_default();
+    let shared = siblings
+        .iter()
+        .filter(|(_, sibling)| sibling.to_lowercase() == name.to_lowercase())
+        .count()
+        > 1;
+
+    // names that could be mistaken for an item ID or for the "Name (id)"
+    // form get the ID too
+    let ambiguous =
+        shared || name.chars().all(|c| c.is_ascii_digit()) || split_id_suffix(name).is_some();
+    match (name.is_empty(), ambiguous) {
+        (true, _) => format!("({id})"),
+        (false, true) => format!("{} ({id})", escape_name(name)),
+        (false, false) => escape_name(name),
+    }
+}
+
+/// Finds the folder among `sub_folders` that `segment` of a display path
+/// stands for.
+fn find_folder_segment(segment: &str, sub_folders: &[(u32, String)]) -> Option<u32> {
+    if let Some((name, id)) = split_id_suffix(segment) {
+        let name = unescape_name(name);
+        let found = sub_folders.iter().find(|(sub_id, sub_name)| {
+            *sub_id == id && sub_name.to_lowercase() == name.to_lowercase()
+        });
+        if let Some((id, _)) = found {
+            return Some(*id);
+        }
+    }
+
+    // an exact match wins over ones that only differ in case
+    let name = unescape_name(segment);
+    let exact = sub_folders
+        .iter()
+        .filter(|(_, sub_name)| *sub_name == name)
+        .collect::<Vec<_>>();
+    let matches = match exact.len() {
+        1 => exact,
+        _ => sub_folders
+            .iter()
+            .filter(|(_, sub_name)| sub_name.to_lowercase() == name.to_lowercase())
+            .collect(),
+    };
+    match matches.as_slice() {
+        [(id, _)] => Some(*id),
+        _ => None,
+    }
+}
+
+/// Splits the `Name (id)` form of a segment into the name and the ID.
+fn split_id_suffix(segment: &str) -> Option<(&str, u32)> {
+    let (name, id) = segment.strip_suffix(')')?.rsplit_once('(')?;
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let name = match name {
+        "" => "",
+        name => name.strip_suffix(' ')?,
+    };
+
+    Some((name, id.parse().ok()?))
+}
+
+fn escape_name(name: &str) -> String {
+    name.replace('%', "%25").replace('/', "%2F")
+}
+
+fn unescape_name(name: &str) -> String {
+    name.replace("%2F", "/")
+        .replace("%2f", "/")
+        .replace("%25", "%")
+}
+
+impl Display for ItemIdPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, id) in self.0.iter().enumerate() {
+            match index {
+                0 => write!(f, "{id}")?,
+                _ => write!(f, "_{id}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ItemIdPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // older versions of `pff-cli index` put a '_' before the ID of items
+        // in the root folder
+        let ids = s.strip_prefix('_').unwrap_or(s);
+        if ids.is_empty() {
+            return Ok(ItemIdPath::default());
+        }
+
+        ids.split('_')
+            .map(|id| id.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map(ItemIdPath)
+            .map_err(|_| Error::BadItemIdPath(s.to_string()))
+    }
+}
+
+impl Serialize for ItemIdPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ItemIdPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        item::{ItemExt, ItemType},
+        path::{find_folder_segment, folder_segment, FolderPath, ItemIdPath},
+        FileOpenFlags, Pff,
+    };
+
+    const TEST_PST_FILE: &str = "../data/sample.ost";
+
+    #[test]
+    fn item_id_path() {
+        let id_path = "8354_8514_8546_7029316".parse::<ItemIdPath>().unwrap();
+        assert_eq!(id_path.ids(), [8354, 8514, 8546, 7029316]);
+        assert_eq!(id_path.id(), Some(7029316));
+        assert_eq!(id_path.to_string(), "8354_8514_8546_7029316");
+
+        assert_eq!("_7029316".parse::<ItemIdPath>().unwrap().ids(), [7029316]);
+        assert_eq!("".parse::<ItemIdPath>().unwrap(), ItemIdPath::default());
+        assert!("8354__7029316".parse::<ItemIdPath>().is_err());
+        assert!("/Inbox".parse::<ItemIdPath>().is_err());
+    }
+
+    #[test]
+    fn folder_path() {
+        assert_eq!(FolderPath::default().to_string(), "/");
+        let folder_path = FolderPath::default().join("Inbox").join("Projects");
+        assert_eq!(folder_path.to_string(), "/Inbox/Projects");
+        assert_eq!(folder_path.depth(), 2);
+    }
+
+    #[test]
+    fn folder_segments() {
+        let siblings = [
+            (1, "Inbox".to_string()),
+            (2, "Projects".to_string()),
+            (3, "projects".to_string()),
+            (4, "2022".to_string()),
+            (5, "Sent/Received".to_string()),
+            (6, String::new()),
+            (7, "Archive (3)".to_string()),
+        ];
+        let segments = [
+            (1, "Inbox"),
+            (2, "Projects (2)"),
+            (3, "projects (3)"),
+            (4, "2022 (4)"),
+            (5, "Sent%2FReceived"),
+            (6, "(6)"),
+            (7, "Archive (3) (7)"),
+        ];
+        for (id, segment) in segments {
+            assert_eq!(folder_segment(id, &siblings), segment);
+            assert_eq!(find_folder_segment(segment, &siblings), Some(id));
+        }
+
+        assert_eq!(find_folder_segment("inbox", &siblings), Some(1));
+        assert_eq!(find_folder_segment("2022", &siblings), Some(4));
+        // a name shared by two folders doesn't say which one is meant
+        assert_eq!(find_folder_segment("PROJECTS", &siblings), None);
+        assert_eq!(find_folder_segment("Projects", &siblings), Some(2));
+        assert_eq!(find_folder_segment("Inbox (2)", &siblings), None);
+        assert_eq!(find_folder_segment("7029316", &siblings), None);
+    }
+
+    #[test]
+    fn resolve() {
+        let pff = Pff::new().unwrap();
+        let pff = pff.open(TEST_PST_FILE, FileOpenFlags::READ).unwrap();
+
+        let root = ItemIdPath::default().resolve(&pff).unwrap();
+        assert_eq!(root.type_().unwrap(), ItemType::Folder);
+        assert_eq!(ItemIdPath::default().display_path(&pff).unwrap(), "/");
+
+        // the empty path used to panic
+        let root = root.into_folder().unwrap();
+        assert!(root.get_item_from_id_path(&[]).unwrap().is_none());
+
+        for sub_folder in root.sub_folders().unwrap() {
+            let id_path = ItemIdPath::new(vec![sub_folder.unwrap().id().unwrap()]);
+            let display_path = id_path.display_path(&pff).unwrap();
+            assert_eq!(
+                ItemIdPath::from_display_path(&pff, &display_path).unwrap(),
+                id_path
+            );
+        }
+
+        assert!(ItemIdPath::new(vec![u32::MAX]).resolve(&pff).is_err());
+    }
+}
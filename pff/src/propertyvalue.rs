@@ -0,0 +1,291 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    filetime::{FileTime, FloatingTime},
+    item::ValueType,
+    multivalue::MultiValue,
+};
+
+/// A decoded record entry value. There is one variant for every
+/// [`ValueType`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Unspecified(Vec<u8>),
+    Null,
+    Integer16BitSigned(i16),
+    Integer32BitSigned(i32),
+    Float32Bit(f32),
+    Double64Bit(f64),
+    /// Fixed point number scaled by 10,000
+    Currency(i64),
+    Floatingtime(NaiveDateTime),
+    /// An `SCODE` error code
+    Error(u32),
+    Boolean(bool),
+    Object(Vec<u8>),
+    Integer64BitSigned(i64),
+    StringAscii(String),
+    StringUnicode(String),
+    Filetime(NaiveDateTime),
+    Guid(Uuid),
+    ServerIdentifier(Vec<u8>),
+    Restriction(Vec<u8>),
+    RuleAction(Vec<u8>),
+    BinaryData(Vec<u8>),
+    MultiValueInteger16BitSigned(Vec<i16>),
+    MultiValueInteger32BitSigned(Vec<i32>),
+    MultiValueFloat32Bit(Vec<f32>),
+    MultiValueDouble64Bit(Vec<f64>),
+    MultiValueCurrency(Vec<i64>),
+    MultiValueFloatingtime(Vec<NaiveDateTime>),
+    MultiValueInteger64BitSigned(Vec<i64>),
+    MultiValueStringAscii(Vec<String>),
+    MultiValueStringUnicode(Vec<String>),
+    MultiValueFiletime(Vec<NaiveDateTime>),
+    MultiValueGuid(Vec<Uuid>),
+    MultiValueBinaryData(Vec<Vec<u8>>),
+}
+
+macro_rules! collect_values {
+    ($values:ident, $value_type:ident, $variant:ident, $scalar_variant:ident) => {
+        PropertyValue::$variant(
+            $values
+                .into_iter()
+                .map(|value| match value {
+                    PropertyValue::$scalar_variant(v) => Ok(v),
+                    _ => Err(Error::BadValueData($value_type)),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+    };
+}
+
+impl PropertyValue {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            PropertyValue::Unspecified(_) => ValueType::Unspecified,
+            PropertyValue::Null => ValueType::Null,
+            PropertyValue::Integer16BitSigned(_) => ValueType::Integer16BitSigned,
+            PropertyValue::Integer32BitSigned(_) => ValueType::Integer32BitSigned,
+            PropertyValue::Float32Bit(_) => ValueType::Float32Bit,
+            PropertyValue::Double64Bit(_) => ValueType::Double64Bit,
+            PropertyValue::Currency(_) => ValueType::Currency,
+            PropertyValue::Floatingtime(_) => ValueType::Floatingtime,
+            PropertyValue::Error(_) => ValueType::Error,
+            PropertyValue::Boolean(_) => ValueType::Boolean,
+            PropertyValue::Object(_) => ValueType::Object,
+            PropertyValue::Integer64BitSigned(_) => ValueType::Integer64BitSigned,
+            PropertyValue::StringAscii(_) => ValueType::StringAscii,
+            PropertyValue::StringUnicode(_) => ValueType::StringUnicode,
+            PropertyValue::Filetime(_) => ValueType::Filetime,
+            PropertyValue::Guid(_) => ValueType::Guid,
+            PropertyValue::ServerIdentifier(_) => ValueType::ServerIdentifier,
+            PropertyValue::Restriction(_) => ValueType::Restriction,
+            PropertyValue::RuleAction(_) => ValueType::RuleAction,
+            PropertyValue::BinaryData(_) => ValueType::BinaryData,
+            PropertyValue::MultiValueInteger16BitSigned(_) => {
+                ValueType::MultiValueInteger16BitSigned
+            }
+            PropertyValue::MultiValueInteger32BitSigned(_) => {
+                ValueType::MultiValueInteger32BitSigned
+            }
+            PropertyValue::MultiValueFloat32Bit(_) => ValueType::MultiValueFloat32Bit,
+            PropertyValue::MultiValueDouble64Bit(_) => ValueType::MultiValueDouble64Bit,
+            PropertyValue::MultiValueCurrency(_) => ValueType::MultiValueCurrency,
+            PropertyValue::MultiValueFloatingtime(_) => ValueType::MultiValueFloatingtime,
+            PropertyValue::MultiValueInteger64BitSigned(_) => {
+                ValueType::MultiValueInteger64BitSigned
+            }
+            PropertyValue::MultiValueStringAscii(_) => ValueType::MultiValueStringAscii,
+            PropertyValue::MultiValueStringUnicode(_) => ValueType::MultiValueStringUnicode,
+            PropertyValue::MultiValueFiletime(_) => ValueType::MultiValueFiletime,
+            PropertyValue::MultiValueGuid(_) => ValueType::MultiValueGuid,
+            PropertyValue::MultiValueBinaryData(_) => ValueType::MultiValueBinaryData,
+        }
+    }
+
+    /// Decodes a single (i.e., not multi-value) value of a fixed size or
+    /// binary type from its raw little endian representation. Strings are
+    /// not handled here since they depend on the codepage of the item.
+    pub(crate) fn from_data(value_type: ValueType, data: &[u8]) -> Result<Self, Error> {
+        Ok(match value_type {
+            ValueType::Null => PropertyValue::Null,
+            ValueType::Integer16BitSigned => {
+                PropertyValue::Integer16BitSigned(i16::from_le_bytes(fixed(value_type, data)?))
+            }
+            ValueType::Integer32BitSigned => {
+                PropertyValue::Integer32BitSigned(i32::from_le_bytes(fixed(value_type, data)?))
+            }
+            ValueType::Float32Bit => {
+                PropertyValue::Float32Bit(f32::from_le_bytes(fixed(value_type, data)?))
+            }
+            ValueType::Double64Bit => {
+                PropertyValue::Double64Bit(f64::from_le_bytes(fixed(value_type, data)?))
+            }
+            ValueType::Currency => {
+                PropertyValue::Currency(i64::from_le_bytes(fixed(value_type, data)?))
+            }
+            ValueType::Floatingtime => PropertyValue::Floatingtime(
+                FloatingTime(f64::from_le_bytes(fixed(value_type, data)?)).try_into()?,
+            ),
+            ValueType::Error => PropertyValue::Error(u32::from_le_bytes(fixed(value_type, data)?)),
+            ValueType::Boolean => PropertyValue::Boolean(fixed::<1>(value_type, data)?[0] != 0),
+            ValueType::Integer64BitSigned => {
+                PropertyValue::Integer64BitSigned(i64::from_le_bytes(fixed(value_type, data)?))
+            }
+            ValueType::Filetime => PropertyValue::Filetime(
+                FileTime(i64::from_le_bytes(fixed(value_type, data)?)).into(),
+            ),
+            ValueType::Guid => PropertyValue::Guid(Uuid::from_bytes_le(fixed(value_type, data)?)),
+            ValueType::Unspecified => PropertyValue::Unspecified(data.to_vec()),
+            ValueType::Object => PropertyValue::Object(data.to_vec()),
+            ValueType::ServerIdentifier => PropertyValue::ServerIdentifier(data.to_vec()),
+            ValueType::Restriction => PropertyValue::Restriction(data.to_vec()),
+            ValueType::RuleAction => PropertyValue::RuleAction(data.to_vec()),
+            ValueType::BinaryData => PropertyValue::BinaryData(data.to_vec()),
+            _ => return Err(Error::BadValueData(value_type)),
+        })
+    }
+
+    pub(crate) fn from_multi_value(
+        value_type: ValueType,
+        multi_value: &MultiValue,
+    ) -> Result<Self, Error> {
+        let values = multi_value
+            .entries()?
+            .map(|entry| entry.and_then(|entry| entry.value()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(match value_type {
+            ValueType::MultiValueInteger16BitSigned => collect_values!(
+                values,
+                value_type,
+                MultiValueInteger16BitSigned,
+                Integer16BitSigned
+            ),
+            ValueType::MultiValueInteger32BitSigned => collect_values!(
+                values,
+                value_type,
+                MultiValueInteger32BitSigned,
+                Integer32BitSigned
+            ),
+            ValueType::MultiValueFloat32Bit => {
+                collect_values!(values, value_type, MultiValueFloat32Bit, Float32Bit)
+            }
+            ValueType::MultiValueDouble64Bit => {
+                collect_values!(values, value_type, MultiValueDouble64Bit, Double64Bit)
+            }
+            ValueType::MultiValueCurrency => {
+                collect_values!(values, value_type, MultiValueCurrency, Currency)
+            }
+            ValueType::MultiValueFloatingtime => {
+                collect_values!(values, value_type, MultiValueFloatingtime, Floatingtime)
+            }
+            ValueType::MultiValueInteger64BitSigned => collect_values!(
+                values,
+                value_type,
+                MultiValueInteger64BitSigned,
+                Integer64BitSigned
+            ),
+            ValueType::MultiValueStringAscii => {
+                collect_values!(values, value_type, MultiValueStringAscii, StringAscii)
+            }
+            ValueType::MultiValueStringUnicode => {
+                collect_values!(values, value_type, MultiValueStringUnicode, StringUnicode)
+            }
+            ValueType::MultiValueFiletime => {
+                collect_values!(values, value_type, MultiValueFiletime, Filetime)
+            }
+            ValueType::MultiValueGuid => collect_values!(values, value_type, MultiValueGuid, Guid),
+            ValueType::MultiValueBinaryData => {
+                collect_values!(values, value_type, MultiValueBinaryData, BinaryData)
+            }
+            _ => return Err(Error::BadValueData(value_type)),
+        })
+    }
+}
+
+fn fixed<const N: usize>(value_type: ValueType, data: &[u8]) -> Result<[u8; N], Error> {
+    data.get(..N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(Error::BadValueData(value_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use uuid::uuid;
+
+    use crate::{item::ValueType, propertyvalue::PropertyValue};
+
+    #[test]
+    fn integers() {
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Integer16BitSigned, &[0xfe, 0xff]).unwrap(),
+            PropertyValue::Integer16BitSigned(-2)
+        );
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Integer32BitSigned, &[1, 2, 0, 0]).unwrap(),
+            PropertyValue::Integer32BitSigned(0x0201)
+        );
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Currency, &50_000i64.to_le_bytes()).unwrap(),
+            PropertyValue::Currency(50_000)
+        );
+    }
+
+    #[test]
+    fn short_data() {
+        PropertyValue::from_data(ValueType::Integer64BitSigned, &[0; 4]).unwrap_err();
+    }
+
+    #[test]
+    fn boolean() {
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Boolean, &[1, 0, 0, 0]).unwrap(),
+            PropertyValue::Boolean(true)
+        );
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Boolean, &[0, 0, 0, 0]).unwrap(),
+            PropertyValue::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn times() {
+        // 2020-01-01T00:00:00
+        let filetime: i64 = 132_223_104_000_000_000;
+        let expected = NaiveDate::from_ymd_opt(2020, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Filetime, &filetime.to_le_bytes()).unwrap(),
+            PropertyValue::Filetime(expected)
+        );
+
+        // 2020-01-01T12:00:00 as days since 1899-12-30
+        let floatingtime: f64 = 43831.5;
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Floatingtime, &floatingtime.to_le_bytes()).unwrap(),
+            PropertyValue::Floatingtime(expected + chrono::Duration::hours(12))
+        );
+        for garbage in [f64::MAX, -1e300, f64::INFINITY] {
+            PropertyValue::from_data(ValueType::Floatingtime, &garbage.to_le_bytes()).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn guid() {
+        let data = [
+            0x29, 0x03, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x46,
+        ];
+        assert_eq!(
+            PropertyValue::from_data(ValueType::Guid, &data).unwrap(),
+            PropertyValue::Guid(uuid!("00020329-0000-0000-c000-000000000046"))
+        );
+    }
+}
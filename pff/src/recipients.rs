@@ -1,19 +1,47 @@
 use std::{fmt::Display, ptr};
 
 use itertools::Itertools;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use pff_sys::{libpff_item_free, libpff_item_t};
 
 use crate::{
+    address::AddressEntries,
     error::Error,
     item::{EntryType, Item, ItemExt},
     recordset::RecordSet,
 };
 
+/// Flags that can be set on top of the recipient type in
+/// `PidTagRecipientType`.
+const RECIPIENT_TYPE_FLAGS: u32 = 0x1000_0000 | 0x8000_0000;
+
+/// How a recipient was addressed, as given by `PidTagRecipientType`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum RecipientKind {
+    Originator = 0,
+    To = 1,
+    Cc = 2,
+    Bcc = 3,
+}
+
+impl Display for RecipientKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RecipientKind::Originator => write!(f, "originator"),
+            RecipientKind::To => write!(f, "to"),
+            RecipientKind::Cc => write!(f, "cc"),
+            RecipientKind::Bcc => write!(f, "bcc"),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Recipient {
     pub email_address: Option<String>,
     pub display_name: Option<String>,
     pub address_type: Option<String>,
+    pub kind: Option<RecipientKind>,
 }
 
 impl Display for Recipient {
@@ -73,43 +101,47 @@ impl Recipients {
 }
 
 fn record_set_to_recipient(rs: RecordSet) -> Result<Recipient, Error> {
-    let mut email_address1 = None;
-    let mut email_address2 = None;
+    let mut address = AddressEntries::default();
     let mut display_name = None;
-    let mut address_type = None;
+    let mut kind = None;
 
     for entry in rs.entries()? {
         let entry = entry?;
 
         match entry.type_() {
             Ok(EntryType::EmailAddress) => {
-                email_address1 = Some(entry.as_string()?);
+                address.email_address = Some(entry.as_string()?);
             }
             Ok(EntryType::EmailAddress2) => {
-                email_address2 = Some(entry.as_string()?);
+                address.smtp_address = Some(entry.as_string()?);
             }
             Ok(EntryType::DisplayName) => {
                 display_name = Some(entry.as_string()?);
             }
             Ok(EntryType::AddressType) => {
-                address_type = Some(entry.as_string()?);
+                address.address_type = Some(entry.as_string()?);
+            }
+            Ok(EntryType::EntryIdentifier) | Ok(EntryType::RecipientEntryIdentifier)
+                if address.entry_id.is_none() =>
+            {
+                address.entry_id = Some(entry.as_buffer()?);
+            }
+            Ok(EntryType::RecipientType) => {
+                // an unexpected type shouldn't cost us the rest of the
+                // recipient so it is left empty instead
+                let recipient_type = entry.as_u32()? & !RECIPIENT_TYPE_FLAGS;
+                kind = RecipientKind::try_from(recipient_type).ok();
             }
             _ => {}
         }
     }
 
-    // For Microsoft Exchange PSTs, the address type is "EX"
-    // and the email address is in EmailAddress2
-    let mut email_address = email_address1;
-    if let Some(at) = address_type.as_ref() {
-        if at.as_str() == "EX" {
-            email_address = email_address2;
-        }
-    }
+    let address_type = address.address_type.clone();
 
     Ok(Recipient {
-        email_address,
+        email_address: address.resolve(),
         display_name,
         address_type,
+        kind,
     })
 }
@@ -4,14 +4,15 @@ use chrono::NaiveDateTime;
 use concat_idents::concat_idents;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use pff_sys::{
-    libpff_error_t, libpff_multi_value_t, libpff_record_entry_free, libpff_record_entry_get_data,
-    libpff_record_entry_get_data_as_16bit_integer, libpff_record_entry_get_data_as_32bit_integer,
-    libpff_record_entry_get_data_as_64bit_integer, libpff_record_entry_get_data_as_boolean,
-    libpff_record_entry_get_data_as_filetime, libpff_record_entry_get_data_as_floating_point,
-    libpff_record_entry_get_data_as_floatingtime, libpff_record_entry_get_data_as_guid,
-    libpff_record_entry_get_data_as_size, libpff_record_entry_get_data_as_utf8_string,
-    libpff_record_entry_get_data_as_utf8_string_size, libpff_record_entry_get_data_size,
-    libpff_record_entry_get_entry_type, libpff_record_entry_get_multi_value,
+    libpff_error_t, libpff_multi_value_t, libpff_name_to_id_map_entry_t, libpff_record_entry_free,
+    libpff_record_entry_get_data, libpff_record_entry_get_data_as_16bit_integer,
+    libpff_record_entry_get_data_as_32bit_integer, libpff_record_entry_get_data_as_64bit_integer,
+    libpff_record_entry_get_data_as_boolean, libpff_record_entry_get_data_as_filetime,
+    libpff_record_entry_get_data_as_floating_point, libpff_record_entry_get_data_as_floatingtime,
+    libpff_record_entry_get_data_as_guid, libpff_record_entry_get_data_as_size,
+    libpff_record_entry_get_data_as_utf8_string, libpff_record_entry_get_data_as_utf8_string_size,
+    libpff_record_entry_get_data_size, libpff_record_entry_get_entry_type,
+    libpff_record_entry_get_multi_value, libpff_record_entry_get_name_to_id_map_entry,
     libpff_record_entry_get_value_type, libpff_record_entry_read_buffer,
     libpff_record_entry_seek_offset, libpff_record_entry_t, libpff_record_set_free,
     libpff_record_set_get_entry_by_index, libpff_record_set_get_entry_by_type,
@@ -24,6 +25,8 @@ use crate::{
     filetime::FileTime,
     item::{EntryType, ValueFlags, ValueType},
     multivalue::MultiValue,
+    namedproperty::{NameToIdMapEntry, NamedProperty},
+    propertyvalue::PropertyValue,
 };
 
 #[derive(Debug)]
@@ -201,6 +204,14 @@ impl RecordEntry {
     }
 
     pub fn type_(&self) -> Result<EntryType, Error> {
+        let entry_type = self.entry_type_id()?;
+        EntryType::try_from(entry_type).map_err(|_| Error::BadEntryType(entry_type))
+    }
+
+    /// Returns the numeric entry type, i.e., the upper 16 bits of the MAPI
+    /// property tag. Unlike [`RecordEntry::type_`] this also works for entry
+    /// types that have no [`EntryType`] variant.
+    pub fn entry_type_id(&self) -> Result<u32, Error> {
         let mut entry_type: u32 = 0;
         let mut error: *mut libpff_error_t = ptr::null_mut();
 
@@ -208,7 +219,7 @@ impl RecordEntry {
             libpff_record_entry_get_entry_type(self.record_entry, &mut entry_type, &mut error)
         };
         match res {
-            1 => Ok(EntryType::try_from(entry_type).map_err(|_| Error::BadEntryType(entry_type))?),
+            1 => Ok(entry_type),
             _ => Err(Error::pff_error(error)),
         }
     }
@@ -348,6 +359,10 @@ impl RecordEntry {
         }
     }
 
+    /// Returns the GUID with its bytes read in the order they are stored,
+    /// i.e., big endian, which is what this has always done. MAPI stores
+    /// GUIDs little endian though, so use [`RecordEntry::value`] to get the
+    /// GUID that Outlook and the property set constants use.
     pub fn as_guid(&self) -> Result<Uuid, Error> {
         let mut buf: [u8; 16] = [0; 16];
         let buf_ptr = buf.as_mut_ptr();
@@ -374,7 +389,32 @@ impl RecordEntry {
         }
     }
 
-    // TODO: libpff_record_entry_get_name_to_id_map_entry
+    /// Decodes the value of this entry according to its value type.
+    pub fn value(&self) -> Result<PropertyValue, Error> {
+        match self.value_type()? {
+            ValueType::StringAscii => Ok(PropertyValue::StringAscii(self.as_string()?)),
+            ValueType::StringUnicode => Ok(PropertyValue::StringUnicode(self.as_string()?)),
+            value_type if value_type.is_multi_value() => {
+                PropertyValue::from_multi_value(value_type, &self.as_multi_value()?)
+            }
+            value_type => PropertyValue::from_data(value_type, &self.as_buffer()?),
+        }
+    }
+
+    /// Returns the property set GUID and name of this entry if its entry
+    /// type is mapped through the file's name-to-ID map.
+    pub fn named_property(&self) -> Result<Option<NamedProperty>, Error> {
+        let mut error: *mut libpff_error_t = ptr::null_mut();
+        let mut entry: *mut libpff_name_to_id_map_entry_t = ptr::null_mut();
+        let res = unsafe {
+            libpff_record_entry_get_name_to_id_map_entry(self.record_entry, &mut entry, &mut error)
+        };
+        match res {
+            0 => Ok(None),
+            1 => Ok(Some(NameToIdMapEntry::new(entry).to_named_property()?)),
+            _ => Err(Error::pff_error(error)),
+        }
+    }
 
     data_get!(u16, as_u16, as_16bit_integer);
     data_get!(u32, as_u32, as_32bit_integer);
@@ -0,0 +1,657 @@
+use bitflags::bitflags;
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveDateTime, Weekday};
+use encoding_rs::WINDOWS_1252;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::appointment::BusyStatus;
+
+/// Value of the end type for a recurrence that ends after a date.
+const END_AFTER_DATE: u32 = 0x2021;
+
+/// Value of the end type for a recurrence that ends after a number of
+/// occurrences.
+const END_AFTER_N_OCCURRENCES: u32 = 0x2022;
+
+/// How often a recurrence repeats as given by `RecurFrequency`, see
+/// [MS-OXOCAL] section 2.2.1.44.1.
+///
+/// [MS-OXOCAL]: https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxocal
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u16)]
+pub enum Frequency {
+    Daily = 0x200a,
+    Weekly = 0x200b,
+    Monthly = 0x200c,
+    Yearly = 0x200d,
+}
+
+bitflags! {
+    /// The days of the week a recurrence falls on.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub struct Weekdays: u32 {
+        const SUNDAY = 0x01;
+        const MONDAY = 0x02;
+        const TUESDAY = 0x04;
+        const WEDNESDAY = 0x08;
+        const THURSDAY = 0x10;
+        const FRIDAY = 0x20;
+        const SATURDAY = 0x40;
+    }
+}
+
+impl Weekdays {
+    /// The days in the set, starting with Sunday.
+    pub fn days(&self) -> Vec<Weekday> {
+        [
+            (Weekdays::SUNDAY, Weekday::Sun),
+            (Weekdays::MONDAY, Weekday::Mon),
+            (Weekdays::TUESDAY, Weekday::Tue),
+            (Weekdays::WEDNESDAY, Weekday::Wed),
+            (Weekdays::THURSDAY, Weekday::Thu),
+            (Weekdays::FRIDAY, Weekday::Fri),
+            (Weekdays::SATURDAY, Weekday::Sat),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| self.contains(*flag))
+        .map(|(_, day)| day)
+        .collect()
+    }
+}
+
+/// The days within the period that a recurrence falls on.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Pattern {
+    /// Every `period` minutes, i.e., every n days.
+    Day,
+    /// On the given days of every `period` weeks.
+    Week(Weekdays),
+    /// On the given day of the month. Day 31 means the last day of months
+    /// that are shorter.
+    Month(u32),
+    /// On the nth (1 to 4, 5 for the last) of the given days of the month,
+    /// for e.g. the second Tuesday.
+    MonthNth(Weekdays, u32),
+    /// On the last day of the month.
+    MonthEnd,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RecurrenceEnd {
+    Date(NaiveDate),
+    Count(u32),
+    Never,
+}
+
+/// A decoded `RecurrencePattern` structure, the part shared by recurring
+/// appointments and tasks.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecurrencePattern {
+    pub frequency: Frequency,
+    pub pattern: Pattern,
+    /// Whether the pattern uses the Hijri calendar instead of the Gregorian
+    /// one.
+    pub hijri: bool,
+    /// The interval: minutes for daily, weeks for weekly and months for
+    /// monthly and yearly recurrences.
+    pub period: u32,
+    pub end: RecurrenceEnd,
+    pub first_day_of_week: Weekday,
+    /// Dates of the instances that were deleted or modified, in local time.
+    pub deleted_instance_dates: Vec<NaiveDate>,
+    /// Dates the modified instances were moved to, in local time.
+    pub modified_instance_dates: Vec<NaiveDate>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl RecurrencePattern {
+    /// Parses `data` as a recurrence pattern. Returns `None` if `data` is malformed
+    /// or uses a pattern we don't know.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        Self::read(&mut Reader { data })
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        let _reader_version = reader.u16()?;
+        let _writer_version = reader.u16()?;
+        let frequency = Frequency::try_from(reader.u16()?).ok()?;
+        let pattern_type = reader.u16()?;
+        let calendar_type = reader.u16()?;
+        let _first_date_time = reader.u32()?;
+        let period = reader.u32()?;
+        let _sliding_flag = reader.u32()?;
+
+        // the Hijri variants of the month patterns are 0x08 higher
+        let pattern = match pattern_type {
+            0x0 => Pattern::Day,
+            0x1 => Pattern::Week(Weekdays::from_bits_truncate(reader.u32()?)),
+            0x2 | 0xa => Pattern::Month(reader.u32()?),
+            0x3 | 0xb => {
+                Pattern::MonthNth(Weekdays::from_bits_truncate(reader.u32()?), reader.u32()?)
+            }
+            0x4 | 0xc => {
+                reader.u32()?;
+                Pattern::MonthEnd
+            }
+            _ => return None,
+        };
+
+        let end_type = reader.u32()?;
+        let occurrence_count = reader.u32()?;
+        let first_day_of_week = weekday_from_sunday(reader.u32()?)?;
+        let deleted_instance_dates = reader.dates()?;
+        let modified_instance_dates = reader.dates()?;
+        let start_date = minutes_to_time(reader.u32()?)?.date();
+        let end_date = minutes_to_time(reader.u32()?)?.date();
+
+        let end = match end_type {
+            END_AFTER_DATE => RecurrenceEnd::Date(end_date),
+            END_AFTER_N_OCCURRENCES => RecurrenceEnd::Count(occurrence_count),
+            _ => RecurrenceEnd::Never,
+        };
+
+        Some(RecurrencePattern {
+            frequency,
+            pattern,
+            hijri: calendar_type == 0x6 || (0xa..=0xc).contains(&pattern_type),
+            period,
+            end,
+            first_day_of_week,
+            deleted_instance_dates,
+            modified_instance_dates,
+            start_date,
+            end_date,
+        })
+    }
+}
+
+bitflags! {
+    /// Which properties of an exception differ from the series.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    struct OverrideFlags: u16 {
+        const SUBJECT = 0x0001;
+        const MEETING_TYPE = 0x0002;
+        const REMINDER_DELTA = 0x0004;
+        const REMINDER = 0x0008;
+        const LOCATION = 0x0010;
+        const BUSY_STATUS = 0x0020;
+        const ATTACHMENT = 0x0040;
+        const SUB_TYPE = 0x0080;
+        const APPOINTMENT_COLOR = 0x0100;
+    }
+}
+
+/// An instance of a recurring appointment that was moved or changed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecurrenceException {
+    /// In local time, like the other times of the recurrence.
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+    /// The start time the instance had before it was changed.
+    pub original_start_time: NaiveDateTime,
+    /// The subject, if it differs from the series.
+    pub subject: Option<String>,
+    /// The location, if it differs from the series.
+    pub location: Option<String>,
+    /// The busy status, if it differs from the series.
+    pub busy_status: Option<BusyStatus>,
+}
+
+/// A decoded `AppointmentRecurrencePattern` structure, the value of
+/// `EntryType::AppointmentRecurrenceData`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AppointmentRecurrence {
+    pub pattern: RecurrencePattern,
+    /// Start time of each instance in minutes after local midnight.
+    pub start_time_offset: u32,
+    /// End time of each instance in minutes after local midnight of the
+    /// instance's start date.
+    pub end_time_offset: u32,
+    pub exceptions: Vec<RecurrenceException>,
+}
+
+impl AppointmentRecurrence {
+    /// Parses `data` as an appointment recurrence. Returns `None` if `data`
+    /// is malformed or uses a pattern we don't know.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader { data };
+        let pattern = RecurrencePattern::read(&mut reader)?;
+        let _reader_version = reader.u32()?;
+        let _writer_version = reader.u32()?;
+        let start_time_offset = reader.u32()?;
+        let end_time_offset = reader.u32()?;
+
+        let count = reader.u16()?;
+        let exceptions = (0..count)
+            .map(|_| read_exception(&mut reader))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(AppointmentRecurrence {
+            pattern,
+            start_time_offset,
+            end_time_offset,
+            exceptions,
+        })
+    }
+
+    /// The start time of the instance on `date`, in local time.
+    pub fn instance_start(&self, date: NaiveDate) -> NaiveDateTime {
+        date.and_time(Default::default()) + Duration::minutes(self.start_time_offset.into())
+    }
+
+    /// The start time of the first instance, in local time.
+    pub fn start_time(&self) -> NaiveDateTime {
+        self.instance_start(self.pattern.start_date)
+    }
+
+    /// The end time of the first instance, in local time.
+    pub fn end_time(&self) -> NaiveDateTime {
+        self.pattern.start_date.and_time(Default::default())
+            + Duration::minutes(self.end_time_offset.into())
+    }
+
+    /// The dates of the instances that were deleted, leaving out the ones
+    /// that were only modified.
+    pub fn deleted_dates(&self) -> Vec<NaiveDate> {
+        self.pattern
+            .deleted_instance_dates
+            .iter()
+            .filter(|date| {
+                !self
+                    .exceptions
+                    .iter()
+                    .any(|exception| exception.original_start_time.date() == **date)
+            })
+            .copied()
+            .collect()
+    }
+}
+
+fn read_exception(reader: &mut Reader) -> Option<RecurrenceException> {
+    let start_time = minutes_to_time(reader.u32()?)?;
+    let end_time = minutes_to_time(reader.u32()?)?;
+    let original_start_time = minutes_to_time(reader.u32()?)?;
+    let flags = OverrideFlags::from_bits_retain(reader.u16()?);
+
+    let mut exception = RecurrenceException {
+        start_time,
+        end_time,
+        original_start_time,
+        subject: None,
+        location: None,
+        busy_status: None,
+    };
+
+    // the optional fields follow in the order of their flags
+    if flags.contains(OverrideFlags::SUBJECT) {
+        exception.subject = Some(reader.ansi_string()?);
+    }
+    if flags.contains(OverrideFlags::MEETING_TYPE) {
+        reader.u32()?;
+    }
+    if flags.contains(OverrideFlags::REMINDER_DELTA) {
+        reader.u32()?;
+    }
+    if flags.contains(OverrideFlags::REMINDER) {
+        reader.u32()?;
+    }
+    if flags.contains(OverrideFlags::LOCATION) {
+        exception.location = Some(reader.ansi_string()?);
+    }
+    if flags.contains(OverrideFlags::BUSY_STATUS) {
+        exception.busy_status = BusyStatus::try_from(reader.u32()?).ok();
+    }
+    if flags.contains(OverrideFlags::ATTACHMENT) {
+        reader.u32()?;
+    }
+    if flags.contains(OverrideFlags::SUB_TYPE) {
+        reader.u32()?;
+    }
+    if flags.contains(OverrideFlags::APPOINTMENT_COLOR) {
+        reader.u32()?;
+    }
+
+    Some(exception)
+}
+
+/// When daylight saving time starts or ends, as stored in a `SYSTEMTIME`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Transition {
+    /// If set the transition happens on `month`/`day` of this year only.
+    pub year: Option<u16>,
+    pub month: u32,
+    pub day_of_week: Weekday,
+    /// The week of the month (1 to 4, 5 for the last) for recurring
+    /// transitions and the day of the month otherwise.
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Transition {
+    fn read(reader: &mut Reader) -> Option<Option<Self>> {
+        let year = reader.u16()?;
+        let month = reader.u16()?;
+        let day_of_week = reader.u16()?;
+        let day = reader.u16()?;
+        let hour = reader.u16()?;
+        let minute = reader.u16()?;
+        let _second = reader.u16()?;
+        let _milliseconds = reader.u16()?;
+
+        // a zero month means there is no daylight saving time
+        if month == 0 {
+            return Some(None);
+        }
+        // recurring transitions give the week of the month
+        if year == 0 && !(1..=5).contains(&day) {
+            return None;
+        }
+
+        Some(Some(Transition {
+            year: (year != 0).then_some(year),
+            month: month.into(),
+            day_of_week: weekday_from_sunday(day_of_week.into())?,
+            day: day.into(),
+            hour: hour.into(),
+            minute: minute.into(),
+        }))
+    }
+
+    /// The local time of the transition in `year`.
+    pub fn time(&self, year: i32) -> Option<NaiveDateTime> {
+        let date = match self.year {
+            Some(_) => NaiveDate::from_ymd_opt(year, self.month, self.day)?,
+            None => {
+                // the nth given weekday of the month, 5 for the last one
+                let first = NaiveDate::from_ymd_opt(year, self.month, 1)?;
+                let offset = (7 + self.day_of_week.num_days_from_sunday()
+                    - first.weekday().num_days_from_sunday())
+                    % 7;
+                let weeks = self.day.checked_sub(1)?;
+                let mut date = first.checked_add_days(Days::new((offset + weeks * 7).into()))?;
+                while date.month() != self.month {
+                    date -= Duration::days(7);
+                }
+                date
+            }
+        };
+
+        date.and_hms_opt(self.hour, self.minute, 0)
+    }
+}
+
+/// A decoded `TimeZoneStruct`, the value of
+/// `EntryType::AppointmentTimezoneStruct`. Recurrences are stored in local
+/// time so this is needed to work out the UTC time of each instance.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TimeZone {
+    /// Minutes to add to the local time to get UTC.
+    pub bias: i32,
+    pub standard_bias: i32,
+    pub daylight_bias: i32,
+    pub standard_date: Option<Transition>,
+    pub daylight_date: Option<Transition>,
+}
+
+impl TimeZone {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader { data };
+        let bias = reader.u32()? as i32;
+        let standard_bias = reader.u32()? as i32;
+        let daylight_bias = reader.u32()? as i32;
+        let _standard_year = reader.u16()?;
+        let standard_date = Transition::read(&mut reader)?;
+        let _daylight_year = reader.u16()?;
+        let daylight_date = Transition::read(&mut reader)?;
+
+        Some(TimeZone {
+            bias,
+            standard_bias,
+            daylight_bias,
+            standard_date,
+            daylight_date,
+        })
+    }
+
+    pub fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        local + Duration::minutes(self.bias_at(local).into())
+    }
+
+    pub fn to_local(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        let daylight = utc - Duration::minutes((self.bias + self.daylight_bias).into());
+        match self.is_daylight(daylight) {
+            true => daylight,
+            false => utc - Duration::minutes((self.bias + self.standard_bias).into()),
+        }
+    }
+
+    fn bias_at(&self, local: NaiveDateTime) -> i32 {
+        match self.is_daylight(local) {
+            true => self.bias + self.daylight_bias,
+            false => self.bias + self.standard_bias,
+        }
+    }
+
+    fn is_daylight(&self, local: NaiveDateTime) -> bool {
+        let (Some(standard), Some(daylight)) = (self.standard_date, self.daylight_date) else {
+            return false;
+        };
+        let (Some(standard), Some(daylight)) =
+            (standard.time(local.year()), daylight.time(local.year()))
+        else {
+            return false;
+        };
+
+        match daylight < standard {
+            // northern hemisphere, daylight saving time is in the summer
+            true => local >= daylight && local < standard,
+            // southern hemisphere, it spans the new year
+            false => local >= daylight || local < standard,
+        }
+    }
+}
+
+/// Converts a day of the week counted from Sunday = 0.
+fn weekday_from_sunday(day: u32) -> Option<Weekday> {
+    match day {
+        0 => Some(Weekday::Sun),
+        1..=6 => Weekday::try_from((day - 1) as u8).ok(),
+        _ => None,
+    }
+}
+
+/// Converts the minutes since 1601-01-01 that recurrences use for dates
+/// and times.
+fn minutes_to_time(minutes: u32) -> Option<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(1601, 1, 1)?
+        .and_hms_opt(0, 0, 0)?
+        .checked_add_signed(Duration::minutes(minutes.into()))
+}
+
+/// Reads the little endian values the recurrence structures are made of.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Option<&[u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Reads a count followed by that many dates.
+    fn dates(&mut self) -> Option<Vec<NaiveDate>> {
+        let count = self.u32()?;
+        (0..count)
+            .map(|_| minutes_to_time(self.u32()?).map(|time| time.date()))
+            .collect()
+    }
+
+    /// Reads an ANSI string that is preceded by its length plus one and its
+    /// length.
+    fn ansi_string(&mut self) -> Option<String> {
+        let _len_plus_one = self.u16()?;
+        let len = self.u16()?;
+        let (value, _) = WINDOWS_1252.decode_without_bom_handling(self.bytes(len.into())?);
+        Some(value.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime, Weekday};
+
+    use crate::{
+        appointment::BusyStatus,
+        recurrence::{
+            AppointmentRecurrence, Frequency, Pattern, RecurrenceEnd, TimeZone, Weekdays,
+        },
+    };
+
+    fn minutes(time: NaiveDateTime) -> u32 {
+        let epoch = NaiveDate::from_ymd_opt(1601, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .unwrap();
+        (time - epoch).num_minutes() as u32
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn time(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        date(year, month, day).and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    /// Every Monday and Wednesday at 09:00 for 10 occurrences, with one
+    /// instance deleted and one moved and renamed.
+    fn weekly_recurrence() -> Vec<u8> {
+        let midnight = |d: NaiveDate| minutes(d.and_hms_opt(0, 0, 0).unwrap());
+        let mut data = vec![];
+        let u16 = |data: &mut Vec<u8>, v: u16| data.extend_from_slice(&v.to_le_bytes());
+        let u32 = |data: &mut Vec<u8>, v: u32| data.extend_from_slice(&v.to_le_bytes());
+
+        u16(&mut data, 0x3004);
+        u16(&mut data, 0x3004);
+        u16(&mut data, 0x200b);
+        u16(&mut data, 0x0001);
+        u16(&mut data, 0x0000);
+        u32(&mut data, 0);
+        u32(&mut data, 1);
+        u32(&mut data, 0);
+        u32(&mut data, 0x02 | 0x08);
+        u32(&mut data, 0x2022);
+        u32(&mut data, 10);
+        u32(&mut data, 1);
+        // deleted (and modified) instances
+        u32(&mut data, 2);
+        u32(&mut data, midnight(date(2022, 3, 9)));
+        u32(&mut data, midnight(date(2022, 3, 14)));
+        u32(&mut data, 1);
+        u32(&mut data, midnight(date(2022, 3, 15)));
+        u32(&mut data, midnight(date(2022, 3, 7)));
+        u32(&mut data, midnight(date(2022, 4, 6)));
+        // the appointment specific part
+        u32(&mut data, 0x3006);
+        u32(&mut data, 0x3009);
+        u32(&mut data, 540);
+        u32(&mut data, 570);
+        u16(&mut data, 1);
+        u32(&mut data, minutes(time(2022, 3, 15, 10, 0)));
+        u32(&mut data, minutes(time(2022, 3, 15, 10, 30)));
+        u32(&mut data, minutes(time(2022, 3, 14, 9, 0)));
+        u16(&mut data, 0x0001 | 0x0020);
+        u16(&mut data, 7);
+        u16(&mut data, 6);
+        data.extend_from_slice(b"Moved!");
+        u32(&mut data, 3);
+
+        data
+    }
+
+    #[test]
+    fn parse_appointment_recurrence() {
+        let recurrence = AppointmentRecurrence::parse(&weekly_recurrence()).unwrap();
+        let pattern = &recurrence.pattern;
+
+        assert_eq!(pattern.frequency, Frequency::Weekly);
+        assert_eq!(
+            pattern.pattern,
+            Pattern::Week(Weekdays::MONDAY | Weekdays::WEDNESDAY)
+        );
+        assert_eq!(pattern.period, 1);
+        assert_eq!(pattern.end, RecurrenceEnd::Count(10));
+        assert_eq!(pattern.first_day_of_week, Weekday::Mon);
+        assert_eq!(pattern.start_date, date(2022, 3, 7));
+
+        assert_eq!(recurrence.start_time(), time(2022, 3, 7, 9, 0));
+        assert_eq!(recurrence.end_time(), time(2022, 3, 7, 9, 30));
+        assert_eq!(recurrence.deleted_dates(), vec![date(2022, 3, 9)]);
+
+        let exception = &recurrence.exceptions[0];
+        assert_eq!(exception.start_time, time(2022, 3, 15, 10, 0));
+        assert_eq!(exception.original_start_time, time(2022, 3, 14, 9, 0));
+        assert_eq!(exception.subject.as_deref(), Some("Moved!"));
+        assert_eq!(exception.busy_status, Some(BusyStatus::OutOfOffice));
+        assert!(exception.location.is_none());
+
+        assert!(AppointmentRecurrence::parse(&weekly_recurrence()[..40]).is_none());
+    }
+
+    #[test]
+    fn time_zone() {
+        // W. Europe Standard Time
+        let mut data = vec![];
+        for bias in [-60i32, 0, -60] {
+            data.extend_from_slice(&bias.to_le_bytes());
+        }
+        for (month, hour) in [(10u16, 3u16), (3, 2)] {
+            data.extend_from_slice(&[0, 0]);
+            for value in [0, month, 0, 5, hour, 0, 0, 0] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        let time_zone = TimeZone::parse(&data).unwrap();
+
+        // the week of the month of a recurring transition can't be 0
+        let mut bad_week = data.clone();
+        bad_week[20..22].copy_from_slice(&0u16.to_le_bytes());
+        assert!(TimeZone::parse(&bad_week).is_none());
+
+        assert_eq!(
+            time_zone.to_utc(time(2022, 1, 10, 9, 0)),
+            time(2022, 1, 10, 8, 0)
+        );
+        assert_eq!(
+            time_zone.to_utc(time(2022, 7, 10, 9, 0)),
+            time(2022, 7, 10, 7, 0)
+        );
+        // daylight saving time started on the last Sunday of March
+        assert_eq!(
+            time_zone.to_utc(time(2022, 3, 27, 9, 0)),
+            time(2022, 3, 27, 7, 0)
+        );
+        assert_eq!(
+            time_zone.to_local(time(2022, 3, 26, 8, 0)),
+            time(2022, 3, 26, 9, 0)
+        );
+        assert_eq!(
+            time_zone.to_local(time(2022, 7, 10, 7, 0)),
+            time(2022, 7, 10, 9, 0)
+        );
+    }
+}
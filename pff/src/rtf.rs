@@ -0,0 +1,512 @@
+//! Compressed RTF as described in [MS-OXRTFCP] and the HTML and plain text
+//! bodies that can be encapsulated in RTF as described in [MS-OXRTFEX].
+//!
+//! [MS-OXRTFCP]: https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxrtfcp
+//! [MS-OXRTFEX]: https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxrtfex
+
+use encoding_rs::{Encoding, WINDOWS_1252};
+
+use crate::error::Error;
+
+/// Size of the compressed RTF header
+const HEADER_LEN: usize = 16;
+
+/// Compression type of LZFu compressed data ("LZFu")
+const COMPRESSED: u32 = 0x7546_5a4c;
+
+/// Compression type of data that is stored as is ("MELA")
+const UNCOMPRESSED: u32 = 0x414c_454d;
+
+/// Size of the LZFu dictionary
+const DICTIONARY_LEN: usize = 4096;
+
+/// The LZFu dictionary starts out with this text
+const INITIAL_DICTIONARY: &[u8] = b"{\\rtf1\\ansi\\mac\\deff0\\deftab720{\\fonttbl;}\
+{\\f0\\fnil \\froman \\fswiss \\fmodern \\fscript \\fdecor MS Sans SerifSymbolArialTimes New RomanCourier\
+{\\colortbl\\red0\\green0\\blue0\r\n\\par \\pard\\plain\\f0\\fs20\\b\\i\\u\\tab\\tx";
+
+/// Groups that hold document metadata rather than content.
+const IGNORED_DESTINATIONS: [&str; 10] = [
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "object",
+    "header",
+    "footer",
+    "listtable",
+    "listoverridetable",
+];
+
+/// Decompresses a compressed RTF body, for e.g. the value of
+/// `EntryType::MessageBodyCompressedRtf`.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.len() < HEADER_LEN {
+        return Err(Error::BadCompressedRtf("header is truncated"));
+    }
+
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ])
+    };
+    let raw_size = read_u32(4) as usize;
+    let compression_type = read_u32(8);
+
+    // the compressed size doesn't include its own field
+    let comp_size = read_u32(0) as usize;
+    if comp_size.saturating_add(4) < HEADER_LEN {
+        return Err(Error::BadCompressedRtf("compressed size is too small"));
+    }
+    let body = &data[HEADER_LEN..data.len().min(comp_size.saturating_add(4))];
+
+    match compression_type {
+        COMPRESSED => decompress_lzfu(body, raw_size),
+        UNCOMPRESSED => Ok(body[..body.len().min(raw_size)].to_vec()),
+        _ => Err(Error::BadCompressedRtf("unknown compression type")),
+    }
+}
+
+fn decompress_lzfu(body: &[u8], raw_size: usize) -> Result<Vec<u8>, Error> {
+    let mut dictionary = [0u8; DICTIONARY_LEN];
+    dictionary[..INITIAL_DICTIONARY.len()].copy_from_slice(INITIAL_DICTIONARY);
+    let mut write_pos = INITIAL_DICTIONARY.len();
+
+    // the raw size comes from the file so don't trust it any further than
+    // the compressed data could plausibly expand to
+    let mut out = Vec::with_capacity(raw_size.min(body.len() * 8));
+    let mut input = body.iter().copied();
+
+    // each control byte says whether the next 8 tokens are literal bytes
+    // (bit clear) or dictionary references (bit set), lowest bit first
+    while let Some(control) = input.next() {
+        for bit in 0..8 {
+            if control & (1 << bit) == 0 {
+                let Some(byte) = input.next() else {
+                    return Ok(out);
+                };
+                out.push(byte);
+                dictionary[write_pos] = byte;
+                write_pos = (write_pos + 1) % DICTIONARY_LEN;
+                continue;
+            }
+
+            let (Some(high), Some(low)) = (input.next(), input.next()) else {
+                return Err(Error::BadCompressedRtf("dictionary reference is truncated"));
+            };
+            let reference = u16::from_be_bytes([high, low]) as usize;
+            let offset = reference >> 4;
+            let length = (reference & 0x0f) + 2;
+
+            // a reference to the current write position marks the end
+            if offset == write_pos {
+                return Ok(out);
+            }
+
+            // the source and destination can overlap so copy a byte at a
+            // time
+            for index in 0..length {
+                let byte = dictionary[(offset + index) % DICTIONARY_LEN];
+                out.push(byte);
+                dictionary[write_pos] = byte;
+                write_pos = (write_pos + 1) % DICTIONARY_LEN;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// A body that was converted to RTF by the mail client and can be recovered
+/// from the RTF.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EncapsulatedBody {
+    Html(String),
+    Text(String),
+}
+
+/// Recovers the original HTML or plain text from an RTF body that
+/// encapsulates it (i.e. one that has the `\fromhtml` or `\fromtext` control
+/// word in its header). Returns `None` for any other RTF.
+pub fn de_encapsulate(rtf: &[u8]) -> Option<EncapsulatedBody> {
+    let mut parser = Parser {
+        tokens: Tokens { data: rtf, pos: 0 },
+        output: Output {
+            text: String::new(),
+            pending: vec![],
+            encoding: WINDOWS_1252,
+        },
+        source: None,
+        states: vec![GroupState::default()],
+        skip_chars: 0,
+    };
+
+    parser.parse();
+    parser.output.flush();
+
+    match parser.source? {
+        Source::Html => Some(EncapsulatedBody::Html(parser.output.text)),
+        Source::Text => Some(EncapsulatedBody::Text(parser.output.text)),
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Source {
+    Html,
+    Text,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Token<'a> {
+    GroupStart,
+    GroupEnd,
+    ControlWord(&'a str, Option<i32>),
+    ControlSymbol(u8),
+    /// A character given as a `\'hh` hex escape
+    Hex(u8),
+    Text(u8),
+}
+
+struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        Tokens {
+            data: self.data,
+            pos: self.pos,
+        }
+        .next()
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+
+            return Some(match byte {
+                b'{' => Token::GroupStart,
+                b'}' => Token::GroupEnd,
+                b'\\' => self.control(),
+                // line breaks in the RTF source carry no meaning
+                b'\r' | b'\n' => continue,
+                byte => Token::Text(byte),
+            });
+        }
+    }
+}
+
+impl<'a> Tokens<'a> {
+    fn control(&mut self) -> Token<'a> {
+        let Some(&first) = self.data.get(self.pos) else {
+            return Token::ControlSymbol(b'\\');
+        };
+
+        if first == b'\'' {
+            let hex = self
+                .data
+                .get(self.pos + 1..self.pos + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            return match hex {
+                Some(byte) => {
+                    self.pos += 3;
+                    Token::Hex(byte)
+                }
+                None => {
+                    self.pos += 1;
+                    Token::ControlSymbol(first)
+                }
+            };
+        }
+
+        if !first.is_ascii_alphabetic() {
+            self.pos += 1;
+            return Token::ControlSymbol(first);
+        }
+
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(u8::is_ascii_alphabetic) {
+            self.pos += 1;
+        }
+        // the name is ASCII letters only so this can't fail
+        let name = std::str::from_utf8(&self.data[start..self.pos]).unwrap_or_default();
+
+        let param_start = self.pos;
+        if self.data.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        while self.data.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        let param = std::str::from_utf8(&self.data[param_start..self.pos])
+            .ok()
+            .and_then(|param| param.parse::<i32>().ok());
+        if param.is_none() {
+            self.pos = param_start;
+        }
+
+        // a space delimits the control word and is part of it
+        if self.data.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+
+        Token::ControlWord(name, param)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct GroupState {
+    /// Inside `\htmlrtf` blocks, which only exist to render the RTF and are
+    /// not part of the original body
+    suppressed: bool,
+    /// Number of characters that follow a `\u` control word as fallback
+    unicode_skip: usize,
+}
+
+impl Default for GroupState {
+    fn default() -> Self {
+        Self {
+            suppressed: false,
+            unicode_skip: 1,
+        }
+    }
+}
+
+struct Output {
+    text: String,
+    /// Bytes in the document's code page that are yet to be decoded
+    pending: Vec<u8>,
+    encoding: &'static Encoding,
+}
+
+impl Output {
+    fn push_byte(&mut self, byte: u8) {
+        self.pending.push(byte);
+    }
+
+    fn push_str(&mut self, value: &str) {
+        self.flush();
+        self.text.push_str(value);
+    }
+
+    fn flush(&mut self) {
+        if !self.pending.is_empty() {
+            let (decoded, _) = self.encoding.decode_without_bom_handling(&self.pending);
+            self.text.push_str(&decoded);
+            self.pending.clear();
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: Tokens<'a>,
+    output: Output,
+    source: Option<Source>,
+    states: Vec<GroupState>,
+    /// Fallback characters of the last `\u` control word still to be skipped
+    skip_chars: usize,
+}
+
+impl Parser<'_> {
+    fn state(&mut self) -> &mut GroupState {
+        // the stack always holds the state of the outermost group
+        let last = self.states.len() - 1;
+        &mut self.states[last]
+    }
+
+    fn parse(&mut self) {
+        while let Some(token) = self.tokens.next() {
+            match token {
+                Token::GroupStart => self.group_start(),
+                Token::GroupEnd => {
+                    if self.states.len() > 1 {
+                        self.states.pop();
+                    }
+                    self.skip_chars = 0;
+                }
+                Token::ControlWord(name, param) => self.control_word(name, param),
+                Token::ControlSymbol(symbol) => match symbol {
+                    b'\\' | b'{' | b'}' => self.text(symbol),
+                    b'~' => self.str("\u{a0}"),
+                    b'_' => self.text(b'-'),
+                    b'\r' | b'\n' => self.str("\r\n"),
+                    _ => {}
+                },
+                Token::Hex(byte) | Token::Text(byte) => self.text(byte),
+            }
+        }
+    }
+
+    fn group_start(&mut self) {
+        let mut state = *self.state();
+
+        match self.tokens.peek() {
+            Some(Token::ControlSymbol(b'*')) => {
+                self.tokens.next();
+                match self.tokens.next() {
+                    // HTML tags are always part of the output even when they
+                    // sit in a suppressed block
+                    Some(Token::ControlWord("htmltag", _)) => state.suppressed = false,
+                    _ => return self.skip_group(),
+                }
+            }
+            Some(Token::ControlWord(name, _)) if IGNORED_DESTINATIONS.contains(&name) => {
+                return self.skip_group();
+            }
+            _ => {}
+        }
+
+        self.states.push(state);
+    }
+
+    /// Skips past the end of the group that was just opened.
+    fn skip_group(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.tokens.next() {
+                Some(Token::GroupStart) => depth += 1,
+                Some(Token::GroupEnd) => depth -= 1,
+                Some(_) => {}
+                None => break,
+            }
+        }
+    }
+
+    fn control_word(&mut self, name: &str, param: Option<i32>) {
+        match name {
+            "fromhtml" => self.source = Some(Source::Html),
+            "fromtext" => self.source = Some(Source::Text),
+            "ansicpg" => {
+                if let Some(encoding) = param
+                    .and_then(|cp| u16::try_from(cp).ok())
+                    .and_then(codepage::to_encoding)
+                {
+                    self.output.flush();
+                    self.output.encoding = encoding;
+                }
+            }
+            "htmlrtf" => self.state().suppressed = param != Some(0),
+            "uc" => self.state().unicode_skip = param.unwrap_or(1).max(0) as usize,
+            "u" => {
+                if let Some(param) = param {
+                    // values over 32767 are written as negative numbers
+                    let code = if param < 0 { param + 0x10000 } else { param };
+                    let c = char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+                    self.str(c.encode_utf8(&mut [0; 4]));
+                    self.skip_chars = self.state().unicode_skip;
+                }
+            }
+            "par" | "line" => self.str("\r\n"),
+            "tab" => self.str("\t"),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, byte: u8) {
+        if self.skip_chars > 0 {
+            self.skip_chars -= 1;
+        } else if !self.state().suppressed {
+            self.output.push_byte(byte);
+        }
+    }
+
+    fn str(&mut self, value: &str) {
+        if !self.state().suppressed {
+            self.output.push_str(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rtf::{de_encapsulate, decompress, EncapsulatedBody};
+
+    #[test]
+    fn decompress_simple() {
+        let data = [
+            0x2d, 0x00, 0x00, 0x00, 0x2b, 0x00, 0x00, 0x00, 0x4c, 0x5a, 0x46, 0x75, 0xf1, 0xc5,
+            0xc7, 0xa7, 0x03, 0x00, 0x0a, 0x00, 0x72, 0x63, 0x70, 0x67, 0x31, 0x32, 0x35, 0x42,
+            0x32, 0x0a, 0xf3, 0x20, 0x68, 0x65, 0x6c, 0x09, 0x00, 0x20, 0x62, 0x77, 0x05, 0xb0,
+            0x6c, 0x64, 0x7d, 0x0a, 0x80, 0x0f, 0xa0,
+        ];
+        assert_eq!(
+            decompress(&data).unwrap(),
+            b"{\\rtf1\\ansi\\ansicpg1252\\pard hello world}\r\n"
+        );
+    }
+
+    #[test]
+    fn decompress_overlapping_reference() {
+        let data = [
+            0x1a, 0x00, 0x00, 0x00, 0x1c, 0x00, 0x00, 0x00, 0x4c, 0x5a, 0x46, 0x75, 0xe2, 0xd4,
+            0x4b, 0x51, 0x41, 0x00, 0x04, 0x20, 0x57, 0x58, 0x59, 0x5a, 0x0d, 0x6e, 0x7d, 0x01,
+            0x0e, 0xb0,
+        ];
+        assert_eq!(decompress(&data).unwrap(), b"{\\rtf1 WXYZWXYZWXYZWXYZWXYZ}");
+    }
+
+    #[test]
+    fn decompress_uncompressed() {
+        let mut data = vec![0x16, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00];
+        data.extend_from_slice(b"MELA");
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(b"{\\rtf1 hi}");
+        assert_eq!(decompress(&data).unwrap(), b"{\\rtf1 hi}");
+        assert!(decompress(&data[..10]).is_err());
+    }
+
+    #[test]
+    fn decompress_bad_header() {
+        // a zeroed header with a body after it
+        assert!(decompress(&[0; 32]).is_err());
+
+        let mut data = vec![0x0b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        data.extend_from_slice(b"LZFu");
+        data.extend_from_slice(&[0; 8]);
+        assert!(decompress(&data).is_err());
+
+        // a raw size far larger than the data can expand to
+        let mut data = vec![0x0f, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff];
+        data.extend_from_slice(b"LZFu");
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&[0x00, b'{', b'}']);
+        assert_eq!(decompress(&data).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn de_encapsulate_html() {
+        let rtf = b"{\\rtf1\\ansi\\ansicpg1252\\fromhtml1 \\deff0{\\fonttbl {\\f0\\fswiss Arial;}}\
+{\\*\\htmltag19 <html>}{\\*\\htmltag2 \\par }{\\*\\htmltag34 <body>}\\htmlrtf {\\htmlrtf0 Hello \\'e9\
+\\htmlrtf \\par \\htmlrtf0}{\\*\\htmltag50 </body>}{\\*\\htmltag27 </html>}}";
+        assert_eq!(
+            de_encapsulate(rtf).unwrap(),
+            EncapsulatedBody::Html("<html>\r\n<body>Hello \u{e9}</body></html>".to_string())
+        );
+    }
+
+    #[test]
+    fn de_encapsulate_text() {
+        let rtf = b"{\\rtf1\\ansi\\fromtext \\deff0{\\fonttbl {\\f0 Arial;}}Line one\\par\r\n\
+Caf\\u233?\\par}";
+        assert_eq!(
+            de_encapsulate(rtf).unwrap(),
+            EncapsulatedBody::Text("Line one\r\nCaf\u{e9}\r\n".to_string())
+        );
+    }
+
+    #[test]
+    fn de_encapsulate_plain_rtf() {
+        assert!(de_encapsulate(b"{\\rtf1\\ansi hello}").is_none());
+    }
+}
@@ -0,0 +1,162 @@
+use std::{fmt::Display, ptr};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use pff_sys::{libpff_item_free, libpff_item_t};
+
+use crate::{
+    error::Error,
+    item::{EntryType, Item, ItemExt},
+    recurrence::RecurrencePattern,
+};
+
+/// The progress of a task as given by `PidLidTaskStatus`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum TaskStatus {
+    NotStarted = 0,
+    InProgress = 1,
+    Complete = 2,
+    /// Waiting on someone else.
+    Waiting = 3,
+    Deferred = 4,
+}
+
+impl Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskStatus::NotStarted => write!(f, "not started"),
+            TaskStatus::InProgress => write!(f, "in progress"),
+            TaskStatus::Complete => write!(f, "complete"),
+            TaskStatus::Waiting => write!(f, "waiting"),
+            TaskStatus::Deferred => write!(f, "deferred"),
+        }
+    }
+}
+
+/// A task item, i.e., an `IPM.Task` message.
+#[derive(Debug)]
+pub struct Task {
+    item: *mut libpff_item_t,
+}
+
+impl Default for Task {
+    fn default() -> Self {
+        Task {
+            item: ptr::null_mut(),
+        }
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        unsafe { libpff_item_free(&mut self.item, ptr::null_mut()) };
+    }
+}
+
+impl Item for Task {
+    fn new(item: *mut libpff_item_t) -> Self {
+        Task { item }
+    }
+
+    fn item(&self) -> *mut libpff_item_t {
+        self.item
+    }
+
+    fn detach(mut self) -> *mut libpff_item_t {
+        let item = self.item;
+        self.item = ptr::null_mut();
+        item
+    }
+}
+
+macro_rules! prop_string {
+    ($method:ident, $entry_type:ident) => {
+        pub fn $method(&self) -> Result<Option<String>, Error> {
+            self.get_string_value(EntryType::$entry_type)
+        }
+    };
+}
+
+impl Task {
+    prop_string!(subject, MessageSubject);
+    prop_string!(body, MessageBodyPlainText);
+
+    pub fn status(&self) -> Result<Option<TaskStatus>, Error> {
+        self.get_u32(EntryType::TaskStatus)?
+            .map(|status| TaskStatus::try_from(status).map_err(|_| Error::BadTaskStatus(status)))
+            .transpose()
+    }
+
+    /// How much of the task is done, from 0 to 100.
+    pub fn percent_complete(&self) -> Result<Option<u32>, Error> {
+        Ok(self
+            .first_entry_by_type(EntryType::TaskPercentageComplete)?
+            .map(|entry| entry.as_f64())
+            .transpose()?
+            .map(|fraction| (fraction.clamp(0.0, 1.0) * 100.0).round() as u32))
+    }
+
+    /// The day work on the task starts. Outlook stores task dates as
+    /// midnight local time, so we round to the nearest day in case the
+    /// value was converted to UTC.
+    pub fn start_date(&self) -> Result<Option<NaiveDate>, Error> {
+        self.get_date(EntryType::TaskStartDate)
+    }
+
+    /// The day the task is due.
+    pub fn due_date(&self) -> Result<Option<NaiveDate>, Error> {
+        self.get_date(EntryType::TaskDueDate)
+    }
+
+    /// When the task was completed in UTC.
+    pub fn date_completed(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::TaskDateCompleted)
+    }
+
+    /// The time spent on the task so far.
+    pub fn actual_effort(&self) -> Result<Option<Duration>, Error> {
+        self.get_minutes(EntryType::TaskActualEffort)
+    }
+
+    /// The time the task is estimated to take.
+    pub fn total_effort(&self) -> Result<Option<Duration>, Error> {
+        self.get_minutes(EntryType::TaskTotalEffort)
+    }
+
+    pub fn is_complete(&self) -> Result<bool, Error> {
+        Ok(self
+            .get_bool(EntryType::TaskIsComplete)?
+            .unwrap_or_default())
+    }
+
+    pub fn is_recurring(&self) -> Result<bool, Error> {
+        Ok(self
+            .get_bool(EntryType::TaskIsRecurring)?
+            .unwrap_or_default())
+    }
+
+    /// The decoded recurrence of a recurring task. Returns `None` if the
+    /// task doesn't recur or the recurrence can't be decoded.
+    pub fn recurrence(&self) -> Result<Option<RecurrencePattern>, Error> {
+        Ok(self
+            .first_entry_by_type(EntryType::TaskRecurrenceData)?
+            .map(|entry| entry.as_buffer())
+            .transpose()?
+            .and_then(|data| RecurrencePattern::parse(&data)))
+    }
+
+    pub fn creation_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::MessageCreationTime)
+    }
+
+    pub fn modification_time(&self) -> Result<Option<NaiveDateTime>, Error> {
+        self.get_time(EntryType::MessageModificationTime)
+    }
+
+    fn get_minutes(&self, entry_type: EntryType) -> Result<Option<Duration>, Error> {
+        Ok(self
+            .get_u32(entry_type)?
+            .map(|minutes| Duration::minutes(minutes.into())))
+    }
+}
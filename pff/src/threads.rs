@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime};
+use uuid::Uuid;
+
+use crate::{error::Error, filetime::FileTime, message::Message};
+
+/// Length of the header block of a conversation index.
+const HEADER_LEN: usize = 22;
+
+/// Length of each child block of a conversation index.
+const CHILD_LEN: usize = 5;
+
+/// Prefixes that mail clients add to the subject of replies and forwards.
+const SUBJECT_PREFIXES: [&str; 8] = ["re", "fw", "fwd", "aw", "wg", "sv", "vs", "antw"];
+
+/// A decoded `PidTagConversationIndex`, see [MS-OXOMSG] section 2.2.1.3.
+/// The header block identifies the conversation and every reply adds a
+/// child block to the index of the message it replies to.
+///
+/// [MS-OXOMSG]: https://learn.microsoft.com/en-us/openspecs/exchange_server_protocols/ms-oxomsg
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConversationIndex {
+    /// Identifies the conversation. All messages in a conversation share it.
+    pub guid: Uuid,
+    /// When the conversation was started.
+    pub start_time: NaiveDateTime,
+    pub responses: Vec<ResponseLevel>,
+}
+
+/// A child block of a conversation index, one per reply.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ResponseLevel {
+    /// Time between the reply and the message before it.
+    pub time_delta: Duration,
+    pub random: u8,
+    pub level: u8,
+}
+
+impl ConversationIndex {
+    /// Parses `data` as a conversation index. Returns `None` if `data` is too
+    /// short to hold the header block. Incomplete child blocks are ignored.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < HEADER_LEN {
+            return None;
+        }
+
+        // the header holds the 6 most significant bytes of a FILETIME
+        let mut filetime = [0; 8];
+        filetime[..6].copy_from_slice(&data[..6]);
+        let start_time = FileTime(i64::from_be_bytes(filetime)).into();
+        let guid = Uuid::from_slice_le(&data[6..HEADER_LEN]).ok()?;
+
+        let responses = data[HEADER_LEN..]
+            .chunks_exact(CHILD_LEN)
+            .map(|block| {
+                let delta = u32::from_be_bytes([block[0], block[1], block[2], block[3]]);
+                // the top bit says how much the remaining 31 bits are shifted
+                let delta = match delta & 0x8000_0000 != 0 {
+                    true => ((delta & 0x7fff_ffff) as i64) << 23,
+                    false => (delta as i64) << 18,
+                };
+                ResponseLevel {
+                    time_delta: Duration::microseconds(delta / 10),
+                    random: block[4] >> 4,
+                    level: block[4] & 0x0f,
+                }
+            })
+            .collect();
+
+        Some(ConversationIndex {
+            guid,
+            start_time,
+            responses,
+        })
+    }
+
+    /// When the message with this index was sent, accurate to a few seconds.
+    pub fn time(&self) -> NaiveDateTime {
+        self.responses
+            .iter()
+            .fold(self.start_time, |time, response| time + response.time_delta)
+    }
+}
+
+/// The properties of a message that are used to put it in a thread.
+#[derive(Debug, Clone, Default)]
+pub struct ThreadInfo {
+    pub conversation_index: Option<ConversationIndex>,
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    /// Message IDs of the earlier messages in the thread, oldest first.
+    pub references: Vec<String>,
+    /// The subject without any reply or forward prefixes, in lower case.
+    pub topic: Option<String>,
+    pub time: Option<NaiveDateTime>,
+}
+
+impl ThreadInfo {
+    pub fn from_message(message: &Message) -> Result<Self, Error> {
+        let headers = message.headers()?.unwrap_or_default();
+        // messages that weren't received over SMTP (for e.g. sent items) have
+        // no transport headers but usually still have a message ID
+        let message_id = headers.message_id().or(message
+            .internet_message_id()?
+            .map(|id| id.trim().trim_matches(['<', '>']).to_string())
+            .filter(|id| !id.is_empty()));
+        let topic = match message.conversation_topic()? {
+            Some(topic) => Some(topic),
+            None => message.subject()?,
+        };
+        let time = match message.delivery_time()? {
+            Some(time) => Some(time),
+            None => message.client_submit_time()?,
+        };
+
+        Ok(ThreadInfo {
+            conversation_index: message.conversation_index()?,
+            message_id,
+            in_reply_to: headers.in_reply_to().into_iter().next(),
+            references: headers.references(),
+            topic: topic.as_deref().and_then(normalize_topic),
+            time,
+        })
+    }
+
+    /// An ID for the thread of this message that can be worked out from the
+    /// message alone. This is the conversation GUID if the message has a
+    /// conversation index, else the message ID of the first message in the
+    /// thread and finally the topic.
+    ///
+    /// Replies written by clients that don't keep the conversation index can
+    /// end up with a different ID than the rest of their thread. Use
+    /// [`Threader`] to group a set of messages taking all of these into
+    /// account.
+    pub fn thread_id(&self) -> Option<String> {
+        if let Some(index) = &self.conversation_index {
+            return Some(format!("conversation:{}", index.guid));
+        }
+
+        self.references
+            .first()
+            .or(self.in_reply_to.as_ref())
+            .or(self.message_id.as_ref())
+            .map(|id| format!("message:{id}"))
+            .or_else(|| self.topic.as_ref().map(|topic| format!("topic:{topic}")))
+    }
+
+    fn sort_time(&self) -> Option<NaiveDateTime> {
+        self.time
+            .or_else(|| self.conversation_index.as_ref().map(|index| index.time()))
+    }
+}
+
+/// A conversation, with its messages ordered oldest first.
+#[derive(Debug)]
+pub struct Thread<K> {
+    pub id: String,
+    pub topic: Option<String>,
+    pub messages: Vec<(K, ThreadInfo)>,
+}
+
+/// Groups messages into threads. Messages are put in the same thread if
+/// they share a conversation GUID or if one refers to the other through
+/// `In-Reply-To` or `References`. Messages that have neither are grouped
+/// by topic.
+#[derive(Debug)]
+pub struct Threader<K> {
+    messages: Vec<(K, ThreadInfo)>,
+}
+
+impl<K> Default for Threader<K> {
+    fn default() -> Self {
+        Threader { messages: vec![] }
+    }
+}
+
+impl<K> Threader<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a message, where `key` is whatever the caller needs to find the
+    /// message again.
+    pub fn add(&mut self, key: K, info: ThreadInfo) {
+        self.messages.push((key, info));
+    }
+
+    /// Returns the threads, ordered by the time of their first message.
+    pub fn threads(self) -> Vec<Thread<K>> {
+        let mut sets = DisjointSets::new(self.messages.len());
+        // maps conversation GUIDs, message IDs and topics to the first
+        // message that has them
+        let mut links: HashMap<String, usize> = HashMap::new();
+        let mut link = |sets: &mut DisjointSets, index: usize, link: String| {
+            let first = *links.entry(link).or_insert(index);
+            sets.union(first, index);
+        };
+
+        for (index, (_, info)) in self.messages.iter().enumerate() {
+            if let Some(conversation_index) = &info.conversation_index {
+                link(&mut sets, index, conversation_index.guid.to_string());
+            }
+
+            let ids = info
+                .message_id
+                .iter()
+                .chain(&info.in_reply_to)
+                .chain(&info.references)
+                .collect::<Vec<_>>();
+            for id in &ids {
+                link(&mut sets, index, format!("<{id}>"));
+            }
+
+            if info.conversation_index.is_none() && ids.is_empty() {
+                if let Some(topic) = &info.topic {
+                    link(&mut sets, index, format!("topic:{topic}"));
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<(K, ThreadInfo)>> = HashMap::new();
+        for (index, message) in self.messages.into_iter().enumerate() {
+            groups.entry(sets.find(index)).or_default().push(message);
+        }
+
+        let mut threads = groups
+            .into_values()
+            .map(|mut messages| {
+                messages.sort_by_key(|(_, info)| (info.sort_time().is_none(), info.sort_time()));
+
+                // prefer an ID based on the conversation GUID since it's what
+                // the per message thread ID is for most messages in a PST
+                let id = messages
+                    .iter()
+                    .find(|(_, info)| info.conversation_index.is_some())
+                    .or(messages.first())
+                    .and_then(|(_, info)| info.thread_id())
+                    .unwrap_or_default();
+                let topic = messages.iter().find_map(|(_, info)| info.topic.clone());
+
+                Thread {
+                    id,
+                    topic,
+                    messages,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        threads.sort_by_key(|thread| {
+            let time = thread
+                .messages
+                .first()
+                .and_then(|(_, info)| info.sort_time());
+            (time.is_none(), time)
+        });
+
+        threads
+    }
+}
+
+/// Union-find over the indices of the messages being threaded.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b.max(a)] = b.min(a);
+        }
+    }
+}
+
+/// Strips reply and forward prefixes such as "Re:" and "AW:" from a subject
+/// and lower cases it. Returns `None` if nothing is left.
+fn normalize_topic(subject: &str) -> Option<String> {
+    let mut topic = subject.trim();
+
+    loop {
+        let stripped = topic.split_once(':').and_then(|(prefix, rest)| {
+            // allow for counters such as "Re[2]:"
+            let prefix = prefix.split('[').next().unwrap_or_default().trim();
+            SUBJECT_PREFIXES
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(prefix))
+                .then_some(rest.trim_start())
+        });
+
+        match stripped {
+            Some(rest) => topic = rest,
+            None => break,
+        }
+    }
+
+    let topic = topic.trim().to_lowercase();
+    (!topic.is_empty()).then_some(topic)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+    use uuid::uuid;
+
+    use crate::threads::{normalize_topic, ConversationIndex, ThreadInfo, Threader};
+
+    const GUID: uuid::Uuid = uuid!("d5b4e0c2-1a2b-4c3d-8e9f-0123456789ab");
+
+    fn conversation_index(responses: &[[u8; 5]]) -> Vec<u8> {
+        // 2022-03-01T10:00:00Z as a FILETIME with the low 2 bytes dropped
+        let filetime = 132_906_024_000_000_000i64.to_be_bytes();
+        let mut data = filetime[..6].to_vec();
+        data.extend_from_slice(&GUID.to_bytes_le());
+        responses.iter().for_each(|r| data.extend_from_slice(r));
+        data
+    }
+
+    #[test]
+    fn parse_conversation_index() {
+        let data = conversation_index(&[[0x00, 0x00, 0x02, 0x3c, 0x31]]);
+        let index = ConversationIndex::parse(&data).unwrap();
+
+        assert_eq!(index.guid, GUID);
+        let start = NaiveDate::from_ymd_opt(2022, 3, 1)
+            .and_then(|d| d.and_hms_opt(10, 0, 0))
+            .unwrap();
+        assert!((index.start_time - start).num_seconds().abs() <= 1);
+        assert_eq!(index.responses.len(), 1);
+        assert_eq!(index.responses[0].random, 3);
+        assert_eq!(index.responses[0].level, 1);
+        // 0x23c << 18 hundred nanoseconds is about 15 seconds
+        assert_eq!(
+            index.responses[0].time_delta,
+            Duration::microseconds(14_994_636)
+        );
+        assert!(index.time() > index.start_time);
+
+        assert!(ConversationIndex::parse(&data[..21]).is_none());
+    }
+
+    #[test]
+    fn topics() {
+        assert_eq!(normalize_topic("Re: AW: Fwd: Budget").unwrap(), "budget");
+        assert_eq!(normalize_topic("RE[2]: Budget").unwrap(), "budget");
+        assert_eq!(
+            normalize_topic("Meeting: agenda").unwrap(),
+            "meeting: agenda"
+        );
+        assert!(normalize_topic("Re: ").is_none());
+    }
+
+    #[test]
+    fn threads() {
+        let message = |id: &str, in_reply_to: Option<&str>, topic: &str| ThreadInfo {
+            message_id: Some(id.to_string()),
+            in_reply_to: in_reply_to.map(str::to_string),
+            topic: Some(topic.to_string()),
+            ..Default::default()
+        };
+
+        let mut threader = Threader::new();
+        threader.add(1, message("a@x", None, "budget"));
+        threader.add(2, message("b@x", Some("a@x"), "budget"));
+        threader.add(3, message("c@x", None, "lunch"));
+        threader.add(
+            4,
+            ThreadInfo {
+                topic: Some("lunch".to_string()),
+                ..Default::default()
+            },
+        );
+        threader.add(
+            5,
+            ThreadInfo {
+                topic: Some("lunch".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let mut threads = threader
+            .threads()
+            .into_iter()
+            .map(|thread| {
+                thread
+                    .messages
+                    .iter()
+                    .map(|(key, _)| *key)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        threads.iter_mut().for_each(|thread| thread.sort());
+        threads.sort();
+
+        assert_eq!(threads, vec![vec![1, 2], vec![3], vec![4, 5]]);
+    }
+}
@@ -0,0 +1,415 @@
+use std::{collections::HashSet, ops::Range};
+
+use crate::{
+    error::Error,
+    folder::Folder,
+    item::{AnyItem, ItemExt, ItemKind},
+    path::{FolderPath, ItemIdPath},
+    PffOpen,
+};
+
+/// An item found by a [`Walker`] along with the path of its folder and its
+/// own ID path. For folders the folder path is the path of the folder
+/// itself.
+pub type WalkEntry = (FolderPath, ItemIdPath, AnyItem);
+
+/// An error that occurred while walking a folder or item.
+#[derive(Debug, thiserror::Error)]
+#[error("{folder_path}: {error}")]
+pub struct WalkError {
+    pub folder_path: FolderPath,
+    /// The ID path of the item that failed, or of its folder if the item
+    /// couldn't be opened.
+    pub id_path: ItemIdPath,
+    /// The ID of the item that failed. `None` if a folder failed or the
+    /// item's ID couldn't be read.
+    pub item_id: Option<u32>,
+    #[source]
+    pub error: Error,
+}
+
+/// Walks the folder tree of a file depth first, yielding every folder and
+/// message below the root folder. A folder's messages come before its sub
+/// folders.
+///
+/// Folders are selected with glob patterns matched against their
+/// [`FolderPath`], for e.g. `/Inbox/**`. `*` matches within a folder name,
+/// `**` matches any number of folders and `?` matches a single character.
+/// Patterns that don't start with `/` match at any depth and matching is
+/// case insensitive.
+///
+/// ```no_run
+/// use pff::{item::ItemKind, walk::Walker, FileOpenFlags, Pff};
+///
+/// let pff = Pff::new()?.open("mailbox.pst", FileOpenFlags::READ)?;
+/// for entry in Walker::new(&pff)
+///     .exclude("/Deleted Items")
+///     .kinds([ItemKind::Email])
+/// {
+///     let (folder_path, id_path, item) = entry?;
+///     println!("{folder_path} {:?} {}", id_path.ids(), item.id()?);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Walker<'a> {
+    pff: &'a PffOpen,
+    include: Vec<Vec<char>>,
+    exclude: Vec<Vec<char>>,
+    kinds: Option<HashSet<ItemKind>>,
+    max_depth: Option<usize>,
+    continue_on_error: bool,
+}
+
+impl<'a> Walker<'a> {
+    pub fn new(pff: &'a PffOpen) -> Self {
+        Walker {
+            pff,
+            include: vec![],
+            exclude: vec![],
+            kinds: None,
+            max_depth: None,
+            continue_on_error: false,
+        }
+    }
+
+    /// Only yields folders, and the items in folders, that match `pattern`
+    /// or any of the other include patterns. Sub folders of folders that
+    /// don't match are still walked.
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(compile(pattern));
+        self
+    }
+
+    /// Skips folders that match `pattern` along with everything below them.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(compile(pattern));
+        self
+    }
+
+    /// Only yields items of the given kinds. Folders are walked either way.
+    pub fn kinds(mut self, kinds: impl IntoIterator<Item = ItemKind>) -> Self {
+        self.kinds.get_or_insert_with(HashSet::new).extend(kinds);
+        self
+    }
+
+    /// Doesn't walk folders deeper than `max_depth` below the root folder,
+    /// so 0 only yields the items in the root folder.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Carries on with the next item after an error instead of stopping
+    /// after yielding it.
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    fn wants(&self, kind: ItemKind) -> bool {
+        self.kinds
+            .as_ref()
+            .is_none_or(|kinds| kinds.contains(&kind))
+    }
+
+    fn is_included(&self, folder_path: &FolderPath) -> bool {
+        let path = folder_path.to_string().chars().collect::<Vec<_>>();
+        self.include.is_empty() || self.include.iter().any(|pattern| glob(pattern, &path))
+    }
+
+    fn is_skipped(&self, folder_path: &FolderPath) -> bool {
+        let path = folder_path.to_string().chars().collect::<Vec<_>>();
+        self.max_depth
+            .is_some_and(|max_depth| folder_path.depth() > max_depth)
+            || self.exclude.iter().any(|pattern| glob(pattern, &path))
+    }
+}
+
+impl<'a> IntoIterator for Walker<'a> {
+    type Item = Result<WalkEntry, WalkError>;
+    type IntoIter = Walk<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Walk {
+            walker: self,
+            stack: vec![],
+            started: false,
+            done: false,
+        }
+    }
+}
+
+/// The iterator of a [`Walker`].
+pub struct Walk<'a> {
+    walker: Walker<'a>,
+    stack: Vec<Frame>,
+    started: bool,
+    done: bool,
+}
+
+/// A folder that is being walked along with the messages and sub folders
+/// that are left.
+struct Frame {
+    folder: Folder,
+    folder_path: FolderPath,
+    id_path: ItemIdPath,
+    messages: Range<i32>,
+    sub_folders: Range<i32>,
+}
+
+impl Frame {
+    fn new(
+        folder: Folder,
+        folder_path: FolderPath,
+        id_path: ItemIdPath,
+        included: bool,
+    ) -> Result<Self, Error> {
+        // the messages of folders that aren't included are skipped
+        let messages = match included {
+            true => 0..folder.messages_count()?,
+            false => 0..0,
+        };
+        let sub_folders = 0..folder.sub_folders_count()?;
+
+        Ok(Frame {
+            folder,
+            folder_path,
+            id_path,
+            messages,
+            sub_folders,
+        })
+    }
+}
+
+impl Walk<'_> {
+    fn fail(
+        &mut self,
+        folder_path: &FolderPath,
+        id_path: ItemIdPath,
+        item_id: Option<u32>,
+        error: Error,
+    ) -> WalkError {
+        if !self.walker.continue_on_error {
+            self.done = true;
+        }
+
+        WalkError {
+            folder_path: folder_path.clone(),
+            id_path,
+            item_id,
+            error,
+        }
+    }
+
+    fn start(&mut self) -> Result<(), Error> {
+        if let Some(root_folder) = self.walker.pff.root_folder()? {
+            let root_folder = root_folder.into_folder()?;
+            let folder_path = FolderPath::default();
+            let included = self.walker.is_included(&folder_path);
+            let frame = Frame::new(root_folder, folder_path, ItemIdPath::default(), included)?;
+            self.stack.push(frame);
+        }
+
+        Ok(())
+    }
+
+    /// Pushes the sub folder at `index` of the current folder, returning it
+    /// as an entry too if it's wanted.
+    fn enter_sub_folder(&mut self, index: i32) -> Result<Option<WalkEntry>, WalkError> {
+        let Some(frame) = self.stack.last() else {
+            return Ok(None);
+        };
+        let (folder_path, id_path) = (frame.folder_path.clone(), frame.id_path.clone());
+
+        match self.open_sub_folder(frame, index) {
+            Ok(Some((entry, frame))) => {
+                self.stack.push(frame);
+                Ok(entry)
+            }
+            Ok(None) => Ok(None),
+            Err(error) => Err(self.fail(&folder_path, id_path, None, error)),
+        }
+    }
+
+    /// Opens the sub folder at `index` of `frame`'s folder. Returns `None` if
+    /// the sub folder is skipped.
+    fn open_sub_folder(
+        &self,
+        frame: &Frame,
+        index: i32,
+    ) -> Result<Option<(Option<WalkEntry>, Frame)>, Error> {
+        let sub_folder = frame.folder.sub_folder(index)?;
+        let folder_path = frame
+            .folder_path
+            .join(sub_folder.name()?.unwrap_or_default());
+        let id_path = frame.id_path.join(sub_folder.id()?);
+        if self.walker.is_skipped(&folder_path) {
+            return Ok(None);
+        }
+
+        let included = self.walker.is_included(&folder_path);
+        let entry = match included && self.walker.wants(ItemKind::Folder) {
+            // the folder is yielded as well as walked so it's opened twice
+            true => Some((
+                folder_path.clone(),
+                id_path.clone(),
+                AnyItem::Folder(frame.folder.sub_folder(index)?),
+            )),
+            false => None,
+        };
+        let frame = Frame::new(sub_folder, folder_path, id_path, included)?;
+
+        Ok(Some((entry, frame)))
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Result<WalkEntry, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if let Err(error) = self.start() {
+                return Some(Err(self.fail(
+                    &FolderPath::default(),
+                    ItemIdPath::default(),
+                    None,
+                    error,
+                )));
+            }
+        }
+
+        while !self.done {
+            let frame = self.stack.last_mut()?;
+
+            if let Some(index) = frame.messages.next() {
+                let message = frame
+                    .folder
+                    .message(index)
+                    .and_then(|message| Ok((message.id()?, message)));
+                let (id_path, item_id, item) = match message {
+                    Ok((id, message)) => (frame.id_path.join(id), Some(id), message.classify()),
+                    Err(error) => (frame.id_path.clone(), None, Err(error)),
+                };
+
+                match item {
+                    Ok(item) if self.walker.wants(item.kind()) => {
+                        return Some(Ok((frame.folder_path.clone(), id_path, item)))
+                    }
+                    Ok(_) => continue,
+                    Err(error) => {
+                        let folder_path = frame.folder_path.clone();
+                        return Some(Err(self.fail(&folder_path, id_path, item_id, error)));
+                    }
+                }
+            }
+
+            if let Some(index) = frame.sub_folders.next() {
+                match self.enter_sub_folder(index) {
+                    Ok(Some(entry)) => return Some(Ok(entry)),
+                    Ok(None) => continue,
+                    Err(error) => return Some(Err(error)),
+                }
+            }
+
+            self.stack.pop();
+        }
+
+        None
+    }
+}
+
+/// Turns a pattern into the characters [`glob`] matches, anchoring patterns
+/// that don't start with `/` at any depth.
+fn compile(pattern: &str) -> Vec<char> {
+    match pattern.starts_with('/') {
+        true => pattern.chars().collect(),
+        false => "**/".chars().chain(pattern.chars()).collect(),
+    }
+}
+
+/// Matches `path` against a glob `pattern`, ignoring case.
+fn glob(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        // any number of whole folders, including none
+        ['*', '*', '/', rest @ ..] => {
+            glob(rest, path)
+                || (0..path.len()).any(|i| path[i] == '/' && glob(rest, &path[i + 1..]))
+        }
+        // the folder itself and everything below it
+        ['/', '*', '*'] => path.is_empty() || path[0] == '/',
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|i| glob(rest, &path[i..])),
+        ['*', rest @ ..] => (0..=path.len())
+            .take_while(|&i| i == 0 || path[i - 1] != '/')
+            .any(|i| glob(rest, &path[i..])),
+        ['?', rest @ ..] => !path.is_empty() && path[0] != '/' && glob(rest, &path[1..]),
+        [c, rest @ ..] => !path.is_empty() && eq_ignore_case(*c, path[0]) && glob(rest, &path[1..]),
+    }
+}
+
+fn eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        item::ItemKind,
+        walk::{compile, glob, Walker},
+        FileOpenFlags, Pff,
+    };
+
+    const TEST_PST_FILE: &str = "../data/sample.ost";
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        glob(&compile(pattern), &path.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn globs() {
+        assert!(matches("/Inbox", "/Inbox"));
+        assert!(matches("/inbox", "/Inbox"));
+        assert!(!matches("/Inbox", "/Inbox/Projects"));
+        assert!(matches("/Inbox/**", "/Inbox"));
+        assert!(matches("/Inbox/**", "/Inbox/Projects/Foo"));
+        assert!(!matches("/Inbox/**", "/Inbox2"));
+        assert!(matches("/Inbox/*", "/Inbox/Projects"));
+        assert!(!matches("/Inbox/*", "/Inbox/Projects/Foo"));
+        assert!(matches("/**/Foo", "/Inbox/Projects/Foo"));
+        assert!(matches("/**/Foo", "/Foo"));
+        assert!(matches("Projects", "/Inbox/Projects"));
+        assert!(matches("Projects", "/Projects"));
+        assert!(!matches("Projects", "/Inbox/Projects/Foo"));
+        assert!(matches("/Proj?cts", "/Projects"));
+        assert!(matches("/Deleted*", "/Deleted Items"));
+    }
+
+    #[test]
+    fn walk() {
+        let pff = Pff::new().unwrap();
+        let pff = pff.open(TEST_PST_FILE, FileOpenFlags::READ).unwrap();
+
+        let folders = Walker::new(&pff)
+            .kinds([ItemKind::Folder])
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(!folders.is_empty());
+        for (folder_path, id_path, item) in &folders {
+            assert_eq!(item.kind(), ItemKind::Folder);
+            assert_eq!(folder_path.depth(), id_path.ids().len());
+            assert_eq!(id_path.id(), Some(item.id().unwrap()));
+        }
+
+        let top_level = Walker::new(&pff)
+            .kinds([ItemKind::Folder])
+            .max_depth(1)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(top_level
+            .iter()
+            .all(|(folder_path, _, _)| folder_path.depth() == 1));
+    }
+}